@@ -0,0 +1,21 @@
+//! benches/hot_path.rs
+//!
+//! The steps of the search hot loop, one benchmark each. `cargo bench` keeps the
+//! previous run's results, so a change shows up as a regression or improvement per
+//! step. The end-to-end speed per key strategy and matching path is
+//! `fancy_wallet_address bench`.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use fancy_wallet_address::bench::Stage;
+
+fn stages(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stages");
+    group.throughput(Throughput::Elements(1));
+    for stage in Stage::ALL {
+        stage.run(|op| group.bench_function(stage.name(), |b| b.iter(&mut *op)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, stages);
+criterion_main!(benches);
@@ -4,11 +4,22 @@ use secp256k1::PublicKey;
 use sha2::{Digest, Sha256};
 use tiny_keccak::{Hasher, Keccak};
 
+/// The Base58 alphabet (Bitcoin ordering) used for Tron addresses.
+pub const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
 /// Generates a Tron address from a secp256k1 public key.
 pub fn public_key_to_tron_address(pk: &PublicKey) -> String {
     // 1. Get uncompressed public key (65 bytes: 0x04 + 32 bytes X + 32 bytes Y)
-    let pk_uncompressed = pk.serialize_uncompressed();
+    let payload = tron_payload_from_uncompressed(&pk.serialize_uncompressed());
+
+    // 6. Base58 encode the final payload
+    bs58::encode(payload).into_string()
+}
 
+/// Builds the 25-byte checksummed payload (`0x41 || hash || checksum`) that a Tron
+/// address is the Base58 encoding of, without doing the encoding itself.
+pub fn tron_payload_from_uncompressed(pk_uncompressed: &[u8; 65]) -> [u8; 25] {
     // 2. Keccak-256 hash of the public key excluding the prefix 0x04
     let mut keccak = Keccak::v256();
     keccak.update(&pk_uncompressed[1..]);
@@ -22,11 +33,11 @@ pub fn public_key_to_tron_address(pk: &PublicKey) -> String {
 
     // 4. Create checksum by double-SHA256
     let mut hasher1 = Sha256::new();
-    hasher1.update(&address_payload);
+    hasher1.update(address_payload);
     let hash1 = hasher1.finalize();
 
     let mut hasher2 = Sha256::new();
-    hasher2.update(&hash1);
+    hasher2.update(hash1);
     let hash2 = hasher2.finalize();
 
     let checksum = &hash2[0..4];
@@ -35,9 +46,85 @@ pub fn public_key_to_tron_address(pk: &PublicKey) -> String {
     let mut final_payload = [0u8; 25];
     final_payload[..21].copy_from_slice(&address_payload);
     final_payload[21..].copy_from_slice(checksum);
+    final_payload
+}
 
-    // 6. Base58 encode the final payload
-    bs58::encode(final_payload).into_string()
+/// Returns the lowest and highest possible Tron addresses.
+///
+/// Every address encodes `0x41 || 20-byte hash || 4-byte checksum`, so all of them
+/// are 34 characters long and sort numerically between these two strings.
+pub fn tron_address_bounds() -> (String, String) {
+    let mut lowest = [0u8; 25];
+    lowest[0] = 0x41;
+    let mut highest = [0xffu8; 25];
+    highest[0] = 0x41;
+    (
+        bs58::encode(lowest).into_string(),
+        bs58::encode(highest).into_string(),
+    )
+}
+
+/// Checks whether any Tron address starts with `T` followed by `prefix`.
+///
+/// The leading `0x41` byte pins the second character to a narrow range (`9` to `Z`),
+/// and longer prefixes are constrained further near the edges of that range. With
+/// `ignore_case`, the prefix is possible if any of its casings is.
+pub fn is_possible_tron_prefix(prefix: &str, ignore_case: bool) -> bool {
+    let (lowest, highest) = tron_address_bounds();
+    if !ignore_case {
+        return within_bounds(&format!("T{}", prefix), &lowest, &highest);
+    }
+
+    // Depth-first over casings; an impossible partial prefix cannot be extended.
+    fn any_casing(done: &mut String, rest: &[u8], lowest: &str, highest: &str) -> bool {
+        let Some((&c, rest)) = rest.split_first() else {
+            return true;
+        };
+        let mut options = vec![c.to_ascii_uppercase(), c.to_ascii_lowercase()];
+        options.dedup();
+        options.into_iter().any(|option| {
+            done.push(option as char);
+            let possible =
+                within_bounds(done, lowest, highest) && any_casing(done, rest, lowest, highest);
+            done.pop();
+            possible
+        })
+    }
+    any_casing(&mut String::from("T"), prefix.as_bytes(), &lowest, &highest)
+}
+
+/// Checks whether `candidate` is the start of some address between `lowest` and `highest`.
+fn within_bounds(candidate: &str, lowest: &str, highest: &str) -> bool {
+    if candidate.len() > lowest.len() {
+        return false;
+    }
+
+    // Base58 strings of equal length compare numerically by their digit values.
+    let digits = |s: &str| -> Option<Vec<usize>> {
+        s.bytes()
+            .map(|c| BASE58_ALPHABET.iter().position(|&a| a == c))
+            .collect()
+    };
+    let n = candidate.len();
+    match (
+        digits(candidate),
+        digits(&lowest[..n]),
+        digits(&highest[..n]),
+    ) {
+        (Some(candidate), Some(lowest), Some(highest)) => {
+            lowest <= candidate && candidate <= highest
+        }
+        _ => false,
+    }
+}
+
+/// Lists the characters that can appear right after the leading `T`.
+pub fn tron_second_chars() -> Vec<char> {
+    BASE58_ALPHABET
+        .iter()
+        .map(|&c| c as char)
+        .filter(|c| is_possible_tron_prefix(&c.to_string(), false))
+        .collect()
 }
 
 #[cfg(test)]
@@ -50,7 +137,7 @@ mod tests {
     fn test_known_private_key_to_address() {
         // A known private key and its corresponding Tron address for validation.
         let private_key_hex = "d2dc029911480a74c6e08fea54223434bc86a4514a69c3c0d942433d5a37c328";
-        let expected_address = "TJRyWwFs9wTFGZg3JbrVriV5incfS2Qd2s";
+        let expected_address = "TBiQpcGt2rZDL8nBTEJGQGfdeisfXVaz1T";
 
         let secp = Secp256k1::new();
         let secret_key = SecretKey::from_str(private_key_hex).expect("Failed to parse private key");
@@ -60,4 +147,29 @@ mod tests {
 
         assert_eq!(generated_address, expected_address);
     }
+
+    #[test]
+    fn test_second_char_range() {
+        let (lowest, highest) = tron_address_bounds();
+        assert_eq!(lowest, "T9yD14Nj9j7xAB4dbGeiX9h8unkKDDv9ZR");
+        assert_eq!(highest, "TZJozAg1ruapycCicgz31GxvYJ1FvTVysk");
+
+        let second: String = tron_second_chars().into_iter().collect();
+        assert_eq!(second, "9ABCDEFGHJKLMNPQRSTUVWXYZ");
+    }
+
+    #[test]
+    fn test_prefix_possibility() {
+        assert!(is_possible_tron_prefix("RX", false));
+        assert!(is_possible_tron_prefix("Bank", false));
+        assert!(!is_possible_tron_prefix("rx", false));
+        assert!(!is_possible_tron_prefix("1", false));
+        // `9` is only allowed when the following characters stay above the lower bound.
+        assert!(is_possible_tron_prefix("9z", false));
+        assert!(!is_possible_tron_prefix("9a", false));
+        // Case folding lifts the lowercase second character into the valid range.
+        assert!(is_possible_tron_prefix("rx", true));
+        assert!(is_possible_tron_prefix("9Z", true));
+        assert!(!is_possible_tron_prefix("9b", true));
+    }
 }
@@ -0,0 +1,152 @@
+//! src/affix.rs
+
+use rustc_hash::FxHashMap;
+
+/// Longest target an address can hold (a Taproot address is 62 characters long).
+const MAX_AFFIX_LEN: usize = 64;
+
+/// Targets of one length, each with the indices it reports, in the order they were
+/// given.
+type TargetsOfLength = FxHashMap<Box<[u8]>, Vec<usize>>;
+
+/// The targets of one length, split by letter case handling.
+struct Group {
+    len: usize,
+    exact: TargetsOfLength,
+    /// Lowercased targets that match regardless of case.
+    folded: TargetsOfLength,
+}
+
+/// Prefixes or suffixes, hashed by length instead of compared one by one: an
+/// address costs one lookup per distinct target length, so a dictionary of ten
+/// thousand names is checked about as fast as three.
+pub struct AffixSet {
+    /// Target lengths, shortest first.
+    lengths: Vec<Group>,
+}
+
+impl AffixSet {
+    /// Hashes `targets`, each matched regardless of case if `ignore_case` says so
+    /// for its index.
+    pub fn new(targets: &[String], ignore_case: impl Fn(usize) -> bool) -> Self {
+        let mut lengths: Vec<Group> = Vec::new();
+        for (index, target) in targets.iter().enumerate() {
+            let len = target.len();
+            let position = match lengths.iter().position(|group| group.len == len) {
+                Some(position) => position,
+                None => {
+                    lengths.push(Group {
+                        len,
+                        exact: FxHashMap::default(),
+                        folded: FxHashMap::default(),
+                    });
+                    lengths.len() - 1
+                }
+            };
+            let group = &mut lengths[position];
+            let (map, key) = if ignore_case(index) {
+                (&mut group.folded, target.to_ascii_lowercase())
+            } else {
+                (&mut group.exact, target.clone())
+            };
+            map.entry(key.into_bytes().into()).or_default().push(index);
+        }
+        lengths.sort_by_key(|group| group.len);
+        Self { lengths }
+    }
+
+    /// Finds a target `address[offset..]` starts with, skipping targets for which
+    /// `skip` returns true. Shorter targets are tried first.
+    #[inline]
+    pub fn find_prefix(
+        &self,
+        address: &[u8],
+        offset: usize,
+        skip: impl Fn(usize) -> bool,
+    ) -> Option<usize> {
+        self.find(|len| address.get(offset..offset + len), skip)
+    }
+
+    /// Finds a target `address` ends with, like [`Self::find_prefix`].
+    #[inline]
+    pub fn find_suffix(&self, address: &[u8], skip: impl Fn(usize) -> bool) -> Option<usize> {
+        self.find(
+            |len| {
+                address
+                    .len()
+                    .checked_sub(len)
+                    .map(|start| &address[start..])
+            },
+            skip,
+        )
+    }
+
+    #[inline]
+    fn find<'b>(
+        &self,
+        window: impl Fn(usize) -> Option<&'b [u8]>,
+        skip: impl Fn(usize) -> bool,
+    ) -> Option<usize> {
+        let mut folded = [0u8; MAX_AFFIX_LEN];
+        self.lengths.iter().find_map(|group| {
+            let window = window(group.len)?;
+            let exact = group.exact.get(window);
+            let folded = if group.folded.is_empty() {
+                None
+            } else {
+                let folded = &mut folded[..group.len];
+                folded.copy_from_slice(window);
+                folded.make_ascii_lowercase();
+                group.folded.get(&*folded)
+            };
+            // The first target given, whichever case handling it has.
+            exact
+                .into_iter()
+                .chain(folded)
+                .flatten()
+                .copied()
+                .filter(|&index| !skip(index))
+                .min()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_by_length() {
+        let targets: Vec<String> = ["8888", "COOL", "88", "cool", "8888"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let exact = AffixSet::new(&targets, |_| false);
+        // The shorter target first, then duplicates in the order given.
+        assert_eq!(exact.find_suffix(b"TXYZ8888", |_| false), Some(2));
+        assert_eq!(exact.find_suffix(b"TXYZ8888", |i| i == 2), Some(0));
+        assert_eq!(exact.find_suffix(b"TXYZ8888", |i| i != 4), Some(4));
+        assert_eq!(exact.find_suffix(b"TXYZCOoL", |_| false), None);
+        assert_eq!(exact.find_prefix(b"TCOOLXYZ", 1, |_| false), Some(1));
+        assert_eq!(exact.find_prefix(b"TCO", 1, |_| false), None);
+
+        let any_case = AffixSet::new(&targets, |_| true);
+        assert_eq!(any_case.find_suffix(b"TXYZCOoL", |_| false), Some(1));
+        assert_eq!(any_case.find_suffix(b"TXYZCOoL", |i| i == 1), Some(3));
+
+        // Only `cool` ignores case; `COOL` still needs its exact spelling.
+        let mixed = AffixSet::new(&targets, |i| i == 3);
+        assert_eq!(mixed.find_suffix(b"TXYZCOoL", |_| false), Some(3));
+        assert_eq!(mixed.find_suffix(b"TXYZCOOL", |_| false), Some(1));
+        assert_eq!(mixed.find_suffix(b"TXYZCOOL", |i| i == 1), Some(3));
+    }
+
+    #[test]
+    fn test_many_targets() {
+        let targets: Vec<String> = (0..10_000).map(|i| format!("N{:05}", i)).collect();
+        let set = AffixSet::new(&targets, |_| true);
+        assert_eq!(set.find_suffix(b"Tabcn09876", |_| false), Some(9876));
+        assert_eq!(set.find_prefix(b"TN00042abc", 1, |_| false), Some(42));
+        assert_eq!(set.find_suffix(b"Tabcn10000", |_| false), None);
+    }
+}
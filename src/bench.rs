@@ -0,0 +1,264 @@
+//! src/bench.rs
+
+use crate::address::{public_key_to_tron_address, tron_payload_from_uncompressed};
+use crate::chain::Chain;
+use crate::residue::{ResidueMatcher, MAX_RESIDUE_SUFFIX_LEN};
+use crate::search::SearchBuilder;
+use crate::walk::{KeyWalker, WALK_BATCH};
+use crate::worker::{CaseOverrides, KeyOptions, Matcher, SearchTargets};
+use secp256k1::rand::thread_rng;
+use secp256k1::Secp256k1;
+use sha2::{Digest, Sha256};
+use std::hint::black_box;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+use tiny_keccak::{Hasher, Keccak};
+
+/// One step of turning a key into a checked Tron address, measured on its own so
+/// that a change to the hot loop can be traced to the step it affects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// A random keypair: one full scalar multiplication (`--strategy random`).
+    Keypair,
+    /// The next public key of a walk: one point addition plus a share of the batch
+    /// inversion (`--strategy walk`).
+    WalkStep,
+    /// Keccak-256 of an uncompressed public key, the Tron and EVM account hash.
+    Keccak,
+    /// Double SHA-256 of the 21-byte payload, the Base58Check checksum.
+    DoubleSha256,
+    /// Checking the payload against an exact suffix's residue, which spares the
+    /// Tron workers the Base58 encoding of almost every key.
+    ResidueMatch,
+    /// Base58 encoding of the 25-byte payload.
+    Base58,
+    /// Looking an address string up in the suffix set, as the workers do when the
+    /// residue check doesn't apply (`--ignore-case`, other chains) or passes.
+    SuffixMatch,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 7] = [
+        Stage::Keypair,
+        Stage::WalkStep,
+        Stage::Keccak,
+        Stage::DoubleSha256,
+        Stage::ResidueMatch,
+        Stage::Base58,
+        Stage::SuffixMatch,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Keypair => "keypair",
+            Stage::WalkStep => "walk step",
+            Stage::Keccak => "keccak",
+            Stage::DoubleSha256 => "double sha256",
+            Stage::ResidueMatch => "residue match",
+            Stage::Base58 => "base58",
+            Stage::SuffixMatch => "suffix match",
+        }
+    }
+
+    /// Sets up random inputs for the stage and hands `measure` a closure that
+    /// performs it once, e.g. to a criterion `Bencher`.
+    pub fn run<R>(self, measure: impl FnOnce(&mut dyn FnMut()) -> R) -> R {
+        let mut rng = thread_rng();
+        let (_, public_key) = Secp256k1::new().generate_keypair(&mut rng);
+        let uncompressed = public_key.serialize_uncompressed();
+        let payload = tron_payload_from_uncompressed(&uncompressed);
+        match self {
+            Stage::Keypair => {
+                let secp = Secp256k1::new();
+                measure(&mut || {
+                    let (mut secret, public_key) = secp.generate_keypair(&mut rng);
+                    black_box(public_key);
+                    secret.non_secure_erase();
+                })
+            }
+            Stage::WalkStep => {
+                let mut walker = KeyWalker::new(&mut rng);
+                let mut index = WALK_BATCH;
+                measure(&mut || {
+                    if index == WALK_BATCH {
+                        walker.advance();
+                        index = 0;
+                    }
+                    black_box(walker.public_key(index));
+                    index += 1;
+                })
+            }
+            Stage::Keccak => measure(&mut || {
+                let mut keccak = Keccak::v256();
+                keccak.update(&black_box(uncompressed)[1..]);
+                let mut hash = [0u8; 32];
+                keccak.finalize(&mut hash);
+                black_box(hash);
+            }),
+            Stage::DoubleSha256 => measure(&mut || {
+                let hash = Sha256::digest(Sha256::digest(&black_box(payload)[..21]));
+                black_box(hash);
+            }),
+            Stage::ResidueMatch => {
+                let residue =
+                    ResidueMatcher::new(&["8888".to_string()]).expect("a short Base58 suffix");
+                measure(&mut || {
+                    black_box(residue.is_match(black_box(&payload)));
+                })
+            }
+            Stage::Base58 => measure(&mut || {
+                black_box(bs58::encode(black_box(payload)).into_string());
+            }),
+            Stage::SuffixMatch => {
+                let targets = SearchTargets {
+                    prefixes: Vec::new(),
+                    suffixes: vec!["8888".to_string()],
+                    narrowing_prefixes: Vec::new(),
+                    narrowed_suffixes: 0,
+                    contains: Vec::new(),
+                    patterns: Vec::new(),
+                    ignore_case: false,
+                    case_overrides: CaseOverrides::default(),
+                    chain: Chain::Tron,
+                    eip55: false,
+                    quotas: vec![None],
+                    split_key: None,
+                    score: None,
+                };
+                let filled = [AtomicBool::new(false)];
+                let matcher = Matcher::new(&targets, &filled);
+                let address = public_key_to_tron_address(&public_key);
+                measure(&mut || {
+                    black_box(matcher.find_match(black_box(&address)));
+                })
+            }
+        }
+    }
+}
+
+/// Runs `stage` on the calling thread for about `duration` and returns how many
+/// times per second it completed.
+pub fn stage_ops_per_second(stage: Stage, duration: Duration) -> f64 {
+    stage.run(|op| {
+        let started = Instant::now();
+        let mut ops: u64 = 0;
+        // Check the clock every few operations, as often as the slowest stage allows.
+        let mut batch = 1;
+        loop {
+            for _ in 0..batch {
+                op();
+            }
+            ops += batch;
+            let elapsed = started.elapsed();
+            if elapsed >= duration {
+                return ops as f64 / elapsed.as_secs_f64();
+            }
+            batch = (batch * 2).min(1024);
+        }
+    })
+}
+
+/// How a benchmark search looks for its target, to compare the workers' matching
+/// paths end to end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Matching {
+    /// An exact suffix, checked on the Tron payload before Base58 encoding.
+    ResidueSuffix,
+    /// A suffix compared as a string, here through `--ignore-case`.
+    StringSuffix,
+    /// The suffix as a regex anchored at the end.
+    Pattern,
+    /// The suffix as a `--contains` substring.
+    Contains,
+}
+
+impl Matching {
+    pub const ALL: [Matching; 4] = [
+        Matching::ResidueSuffix,
+        Matching::StringSuffix,
+        Matching::Pattern,
+        Matching::Contains,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Matching::ResidueSuffix => "residue suffix",
+            Matching::StringSuffix => "string suffix",
+            Matching::Pattern => "regex",
+            Matching::Contains => "contains",
+        }
+    }
+
+    /// Whether the matching path exists on `chain`: residues are for Tron only, and
+    /// elsewhere an exact suffix is compared as a string.
+    pub fn applies_to(self, chain: Chain) -> bool {
+        self != Matching::ResidueSuffix || chain == Chain::Tron
+    }
+}
+
+/// A suffix no search will find in practice (the alphabet's last character, as
+/// many times as a residue still covers), so that a benchmark runs the usual
+/// matching path for its whole duration.
+fn unmatchable_suffix(chain: Chain) -> String {
+    let alphabet = chain.alphabet();
+    let last = alphabet[alphabet.len() - 1] as char;
+    last.to_string().repeat(MAX_RESIDUE_SUFFIX_LEN)
+}
+
+/// Measures how many keys per second a CPU search checks on `chain`, by running
+/// one for `duration`. Worker start-up counts against the result, as it does for a
+/// real search. The GPU worker is left out: it only checks one key per batch so
+/// far, so its figure would mean nothing.
+pub fn keys_per_second(
+    chain: Chain,
+    keys: KeyOptions,
+    matching: Matching,
+    threads: usize,
+    duration: Duration,
+) -> Result<f64, String> {
+    let target = unmatchable_suffix(chain);
+    let builder = SearchBuilder::new(chain)
+        .strategy(keys.strategy)
+        .mnemonic_words(keys.mnemonic_words)
+        .mnemonic_indices(keys.mnemonic_indices)
+        .threads(threads)
+        .max_time(duration);
+    let builder = match matching {
+        Matching::ResidueSuffix => builder.suffix(target),
+        Matching::StringSuffix => builder.suffix(target).ignore_case(true),
+        Matching::Pattern => builder.pattern(format!("re:{}$", target)),
+        Matching::Contains => builder.contains(target),
+    };
+    let mut handle = builder.build()?.start();
+    // Nothing is found, so this only returns once the time is up.
+    handle.by_ref().for_each(drop);
+    Ok(handle.stats().speed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::KeyStrategy;
+
+    #[test]
+    fn test_every_stage_runs() {
+        for stage in Stage::ALL {
+            assert!(stage_ops_per_second(stage, Duration::from_millis(10)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_every_matching_runs() {
+        let keys = KeyOptions {
+            strategy: KeyStrategy::Walk,
+            mnemonic_words: 12,
+            mnemonic_indices: 1,
+        };
+        for chain in [Chain::Tron, Chain::Evm, Chain::BtcSegwit] {
+            for matching in Matching::ALL {
+                let speed = keys_per_second(chain, keys, matching, 1, Duration::from_millis(50));
+                assert!(speed.unwrap() > 0.0, "{:?} on {:?}", matching, chain);
+            }
+        }
+    }
+}
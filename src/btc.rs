@@ -0,0 +1,130 @@
+//! src/btc.rs
+
+use bech32::hrp;
+use ripemd::Ripemd160;
+use secp256k1::{Scalar, XOnlyPublicKey};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// The bech32 data alphabet used by SegWit and Taproot addresses.
+pub const BECH32_ALPHABET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Compresses an uncompressed SEC1 public key (`0x04 || X || Y`) to `0x02/0x03 || X`.
+pub fn compress(pk_uncompressed: &[u8; 65]) -> [u8; 33] {
+    let mut compressed = [0u8; 33];
+    compressed[0] = 0x02 | (pk_uncompressed[64] & 1);
+    compressed[1..].copy_from_slice(&pk_uncompressed[1..33]);
+    compressed
+}
+
+/// RIPEMD-160 of SHA-256, the key hash of P2PKH and P2WPKH outputs.
+fn hash160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+/// Legacy pay-to-pubkey-hash address (`1...`) of the compressed key.
+pub fn p2pkh_address(pk_uncompressed: &[u8; 65]) -> String {
+    let mut payload = [0u8; 21];
+    payload[1..].copy_from_slice(&hash160(&compress(pk_uncompressed)));
+    bs58::encode(payload).with_check().into_string()
+}
+
+/// Native SegWit pay-to-witness-pubkey-hash address (`bc1q...`).
+pub fn p2wpkh_address(pk_uncompressed: &[u8; 65]) -> String {
+    bech32::segwit::encode_v0(hrp::BC, &hash160(&compress(pk_uncompressed)))
+        .expect("a 20-byte program is a valid witness program")
+}
+
+/// Taproot address (`bc1p...`) for a key-path-only output, as in BIP 86.
+///
+/// The output key is the internal key (with even Y) tweaked by
+/// `tagged_hash("TapTweak", x)`, which costs a point multiplication per key.
+pub fn p2tr_address(pk_uncompressed: &[u8; 65]) -> String {
+    let internal =
+        XOnlyPublicKey::from_slice(&pk_uncompressed[1..33]).expect("valid public key coordinate");
+    let tweak = Scalar::from_be_bytes(tagged_hash(b"TapTweak", &pk_uncompressed[1..33]))
+        .expect("tweak hash is below the curve order");
+    let (output, _) = internal
+        .add_tweak(secp256k1::SECP256K1, &tweak)
+        .expect("tweaked key is valid");
+    bech32::segwit::encode_v1(hrp::BC, &output.serialize())
+        .expect("a 32-byte program is a valid witness program")
+}
+
+/// BIP 340 tagged hash: `SHA256(SHA256(tag) || SHA256(tag) || data)`.
+fn tagged_hash(tag: &[u8], data: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new()
+        .chain_update(tag_hash)
+        .chain_update(tag_hash)
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+/// Encodes a private key in Wallet Import Format for mainnet, flagged as
+/// belonging to a compressed public key (`K...` / `L...`). The caller owns
+/// zeroizing the result.
+pub fn to_wif(secret: &[u8; 32]) -> String {
+    let mut payload = [0u8; 34];
+    payload[0] = 0x80;
+    payload[1..33].copy_from_slice(secret);
+    payload[33] = 0x01;
+    let wif = bs58::encode(payload).with_check().into_string();
+    payload.zeroize();
+    wif
+}
+
+/// Decodes a mainnet WIF private key (compressed or not) back to its 32 bytes.
+/// The caller owns zeroizing the result.
+pub fn from_wif(wif: &str) -> Option<[u8; 32]> {
+    let mut payload = bs58::decode(wif).with_check(None).into_vec().ok()?;
+    let secret = match payload.as_slice() {
+        [0x80, key @ .., 0x01] | [0x80, key @ ..] if key.len() == 32 => key.try_into().ok(),
+        _ => None,
+    };
+    payload.zeroize();
+    secret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Secp256k1, SecretKey};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_known_private_key_to_addresses() {
+        let secp = Secp256k1::new();
+        let secret_key =
+            SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap();
+        let public_key = secret_key.public_key(&secp).serialize_uncompressed();
+
+        assert_eq!(
+            p2pkh_address(&public_key),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        assert_eq!(
+            p2wpkh_address(&public_key),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            p2tr_address(&public_key),
+            "bc1pmfr3p9j00pfxjh0zmgp99y8zftmd3s5pmedqhyptwy6lm87hf5sspknck9"
+        );
+        assert_eq!(
+            to_wif(&secret_key.secret_bytes()),
+            "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"
+        );
+        assert_eq!(
+            from_wif("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"),
+            Some(secret_key.secret_bytes())
+        );
+        // The uncompressed form of the same key.
+        assert_eq!(
+            from_wif("5HpHagT65TZzG1PH3CSu63k8DbpvD8s5ip4nEB3kEsreAnchuDf"),
+            Some(secret_key.secret_bytes())
+        );
+    }
+}
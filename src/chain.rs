@@ -0,0 +1,217 @@
+//! src/chain.rs
+
+use crate::address::{tron_payload_from_uncompressed, tron_second_chars, BASE58_ALPHABET};
+use crate::btc::{p2pkh_address, p2tr_address, p2wpkh_address, BECH32_ALPHABET};
+use crate::evm::{evm_address_bytes, to_checksum_address};
+
+/// Hex digits in both cases; EVM addresses only differ in case through EIP-55.
+const HEX_ALPHABET: &[u8; 22] = b"0123456789abcdefABCDEF";
+
+/// The address format a search generates.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Chain {
+    /// Tron (TRX): Base58Check addresses starting with `T`.
+    #[default]
+    Tron,
+    /// Ethereum and other EVM chains: `0x`-prefixed hex addresses.
+    Evm,
+    /// Bitcoin legacy P2PKH: Base58Check addresses starting with `1`.
+    Btc,
+    /// Bitcoin native SegWit P2WPKH: bech32 addresses starting with `bc1q`.
+    BtcSegwit,
+    /// Bitcoin Taproot P2TR: bech32m addresses starting with `bc1p`.
+    BtcTaproot,
+}
+
+impl Chain {
+    /// Human-readable name for messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Chain::Tron => "Tron",
+            Chain::Evm => "EVM",
+            Chain::Btc => "Bitcoin",
+            Chain::BtcSegwit => "Bitcoin SegWit",
+            Chain::BtcTaproot => "Bitcoin Taproot",
+        }
+    }
+
+    /// The fixed start of every address; prefixes are matched right after it.
+    pub fn lead(self) -> &'static str {
+        match self {
+            Chain::Tron => "T",
+            Chain::Evm => "0x",
+            Chain::Btc => "1",
+            Chain::BtcSegwit => "bc1q",
+            Chain::BtcTaproot => "bc1p",
+        }
+    }
+
+    /// Length of every address, including the lead. Legacy Bitcoin addresses can be
+    /// shorter; this is their maximum.
+    pub fn address_len(self) -> usize {
+        match self {
+            Chain::Tron | Chain::Btc => 34,
+            Chain::Evm | Chain::BtcSegwit => 42,
+            Chain::BtcTaproot => 62,
+        }
+    }
+
+    /// Length of the shortest possible address, including the lead.
+    pub fn shortest_address_len(self) -> usize {
+        match self {
+            Chain::Btc => 26,
+            _ => self.address_len(),
+        }
+    }
+
+    /// Characters that can follow the lead.
+    pub fn alphabet(self) -> &'static [u8] {
+        match self {
+            Chain::Tron | Chain::Btc => BASE58_ALPHABET,
+            Chain::Evm => HEX_ALPHABET,
+            Chain::BtcSegwit | Chain::BtcTaproot => BECH32_ALPHABET,
+        }
+    }
+
+    /// Characters an address body is made of, repeated by how often they occur.
+    /// Used for difficulty estimates.
+    pub fn char_pool(self, eip55: bool) -> Vec<char> {
+        match (self, eip55) {
+            (Chain::Tron | Chain::Btc | Chain::BtcSegwit | Chain::BtcTaproot, _) => {
+                self.alphabet().iter().map(|&c| c as char).collect()
+            }
+            (Chain::Evm, false) => "0123456789abcdef".chars().collect(),
+            // Under EIP-55 each hex letter is upper- or lowercase with equal odds,
+            // while digits always keep their single form.
+            (Chain::Evm, true) => "01234567890123456789abcdefABCDEF".chars().collect(),
+        }
+    }
+
+    /// Like [`Self::char_pool`], for the first character after the lead. Tron pins
+    /// it to a narrow range because of the `0x41` version byte.
+    pub fn first_char_pool(self, eip55: bool) -> Vec<char> {
+        match self {
+            Chain::Tron => tron_second_chars(),
+            // The 24 bytes after the zero version byte are below 2^192 ≈ 23.7 · 58^32,
+            // so ~96% of legacy addresses are 34 long and continue with `2`..`Q`; the
+            // short rest continue with any character. Weight the pool to match.
+            Chain::Btc => {
+                let mut pool = self.char_pool(eip55);
+                for _ in 1..58 {
+                    pool.extend(BASE58_ALPHABET[1..=23].iter().map(|&c| c as char));
+                }
+                pool
+            }
+            _ => self.char_pool(eip55),
+        }
+    }
+
+    /// Recognizes the address type from its fixed start, e.g. for `verify`.
+    pub fn from_address(address: &str) -> Option<Chain> {
+        [
+            Chain::BtcSegwit,
+            Chain::BtcTaproot,
+            Chain::Evm,
+            Chain::Tron,
+            Chain::Btc,
+        ]
+        .into_iter()
+        .find(|chain| {
+            let lead = chain.lead();
+            address.len() > lead.len()
+                && address
+                    .get(..lead.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(lead))
+        })
+    }
+
+    /// Whether addresses are written in a single case, so matching should ignore it
+    /// (plain EVM hex, and bech32 which is lowercase by convention).
+    pub fn folds_case(self, eip55: bool) -> bool {
+        match self {
+            Chain::Tron | Chain::Btc => false,
+            Chain::Evm => !eip55,
+            Chain::BtcSegwit | Chain::BtcTaproot => true,
+        }
+    }
+
+    /// Whether this is one of the Bitcoin address types, whose keys are shown as WIF.
+    pub fn is_bitcoin(self) -> bool {
+        matches!(self, Chain::Btc | Chain::BtcSegwit | Chain::BtcTaproot)
+    }
+
+    /// Suggests a valid look-alike for a character outside the alphabet.
+    pub fn look_alike(self, c: char) -> Option<char> {
+        match (self, c) {
+            (Chain::Tron | Chain::Btc, '0' | 'O') => Some('o'),
+            (Chain::Tron | Chain::Btc, 'I') => Some('i'),
+            (Chain::Tron | Chain::Btc, 'l') => Some('L'),
+            (Chain::Evm, 'o' | 'O') => Some('0'),
+            (Chain::Evm, 'l' | 'I' | 'i') => Some('1'),
+            (Chain::BtcSegwit | Chain::BtcTaproot, 'o' | 'O') => Some('0'),
+            (Chain::BtcSegwit | Chain::BtcTaproot, '1' | 'i' | 'I') => Some('l'),
+            _ => None,
+        }
+    }
+
+    /// BIP44-style `(purpose, coin type)` wallets derive this address type under,
+    /// i.e. `m/purpose'/coin'/0'/0/i`.
+    pub fn bip44_path(self) -> (u32, u32) {
+        match self {
+            Chain::Tron => (44, 195),
+            Chain::Evm => (44, 60),
+            Chain::Btc => (44, 0),
+            Chain::BtcSegwit => (84, 0),
+            Chain::BtcTaproot => (86, 0),
+        }
+    }
+
+    /// Encodes an uncompressed public key the way wallets display it
+    /// (Base58Check for Tron and legacy Bitcoin, EIP-55 checksummed hex for EVM,
+    /// bech32 for SegWit and Taproot).
+    pub fn address_from_uncompressed(self, pk_uncompressed: &[u8; 65]) -> String {
+        match self {
+            Chain::Tron => {
+                bs58::encode(tron_payload_from_uncompressed(pk_uncompressed)).into_string()
+            }
+            Chain::Evm => to_checksum_address(&evm_address_bytes(pk_uncompressed)),
+            Chain::Btc => p2pkh_address(pk_uncompressed),
+            Chain::BtcSegwit => p2wpkh_address(pk_uncompressed),
+            Chain::BtcTaproot => p2tr_address(pk_uncompressed),
+        }
+    }
+
+    /// The raw bytes an address encodes, in hex: the versioned hash for Base58Check
+    /// addresses (`41...` for Tron, `00...` for legacy Bitcoin), the 20-byte account
+    /// hash for EVM, and the witness program for SegWit and Taproot.
+    pub fn hex_address(self, address: &str) -> Option<String> {
+        match self {
+            Chain::Tron | Chain::Btc => bs58::decode(address)
+                .with_check(None)
+                .into_vec()
+                .ok()
+                .map(hex::encode),
+            Chain::Evm => address.strip_prefix("0x").map(str::to_lowercase),
+            Chain::BtcSegwit | Chain::BtcTaproot => bech32::segwit::decode(address)
+                .ok()
+                .map(|(_, _, program)| hex::encode(program)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_address() {
+        assert_eq!(
+            Chain::from_address("TN7T888H5XWecmwVfJyCL9JbcrZEteiK7A"),
+            Some(Chain::Tron)
+        );
+        assert_eq!(Chain::from_address("BC1QXY"), Some(Chain::BtcSegwit));
+        assert_eq!(Chain::from_address("0x"), None);
+        // The lead is compared by bytes, which may end inside a character.
+        assert_eq!(Chain::from_address("ab€x"), None);
+    }
+}
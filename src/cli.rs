@@ -1,23 +1,176 @@
 //! src/cli.rs
 
-use clap::Parser;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand};
+use fancy_wallet_address::chain::Chain;
+use fancy_wallet_address::contains::split_positions;
+use fancy_wallet_address::keystore::KeystoreKdf;
+use fancy_wallet_address::output::OutputFormat;
+use fancy_wallet_address::patterns_file;
+use fancy_wallet_address::quota::split_quota;
+use fancy_wallet_address::score::BeautyScore;
+use fancy_wallet_address::search::{SearchBuilder, Target, TargetKind};
+use fancy_wallet_address::worker::KeyStrategy;
+use secp256k1::{PublicKey, SecretKey};
+use std::path::PathBuf;
+use std::time::Duration;
 
-/// A high-performance Tron (TRX) vanity address generator.
+/// A high-performance Tron (TRX), EVM and Bitcoin vanity address generator.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// What addresses to look for, shared by `search` and `estimate`.
+#[derive(Args, Debug)]
+pub struct TargetArgs {
     /// Comma-separated list of desired address suffixes (e.g., "8888,COOL,Tron").
-    #[arg(long, required = true, value_delimiter = ',')]
+    /// Append `:N` to ask for N addresses of one suffix (e.g., "8888:3,COOL:1").
+    #[arg(long, required_unless_present_any = ["prefixes", "contains", "patterns", "patterns_file", "score"], value_delimiter = ',')]
     pub suffixes: Vec<String>,
 
-    /// The number of addresses to find before exiting.
+    /// Comma-separated list of desired prefixes, matched right after the leading `T`
+    /// (e.g., "RX,Bank"). Combined with --suffixes, an address must match both.
+    #[arg(long, value_delimiter = ',')]
+    pub prefixes: Vec<String>,
+
+    /// Comma-separated list of substrings wanted anywhere after the lead (e.g., "COOL,8888").
+    /// Append `@START..END` to only accept starts from START up to END (0 is the first
+    /// character after the lead; `@5` is exactly there), and `:N` for a quota.
+    #[arg(long, value_delimiter = ',')]
+    pub contains: Vec<String>,
+
+    /// Regex (`^T.{5}KiWi`) or glob (`T*88?88`) matched against the whole address.
+    /// May be repeated; each pattern is an alternative to the prefix/suffix target.
+    /// Use a `re:` or `glob:` prefix to force how the pattern is read, and a `:N`
+    /// suffix for a quota.
+    #[arg(long = "pattern")]
+    pub patterns: Vec<String>,
+
+    /// Reads targets from a file, one per line as `[~|=][prefix:|suffix:|contains:]TARGET[:N] [LABEL]`
+    /// (`~` any letter case, `=` exact case), or as `.toml`/`.json` entries with a type,
+    /// quota, ignore_case and label. Every entry is a target of its own, and its label
+    /// is reported with every address found for it.
+    #[arg(long, value_name = "FILE")]
+    pub patterns_file: Option<PathBuf>,
+
+    /// Instead of matching targets, score every address for runs (`88888`), repeated
+    /// pairs (`abab`), ascending or descending sequences (`12345`) and mirrored tails
+    /// (`xyzyx`), and keep the best. Runs until --max-time, --max-attempts or Ctrl-C.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["suffixes", "prefixes", "contains", "patterns", "patterns_file"])]
+    pub score: bool,
+
+    /// How many of the best-scoring addresses `--score` keeps.
+    #[arg(long, default_value_t = 10, requires = "score")]
+    pub top: usize,
+
+    /// Match prefixes, suffixes, substrings and patterns in any letter case (e.g., "cool" also finds "CoOL").
+    #[arg(long, default_value_t = false)]
+    pub ignore_case: bool,
+
+    /// Which kind of address to generate. Prefixes are matched after the fixed
+    /// start of the address (`T` for Tron, `0x` for EVM, `1`, `bc1q` or `bc1p` for
+    /// Bitcoin legacy, SegWit and Taproot).
+    #[arg(long, value_enum, default_value_t = Chain::Tron)]
+    pub chain: Chain,
+
+    /// EVM only: match the EIP-55 checksummed address exactly, letter case included.
+    /// Without it, hex patterns match in any case.
+    #[arg(long, default_value_t = false)]
+    pub eip55: bool,
+
+    /// The number of addresses to find before exiting. Ignored when per-pattern
+    /// quotas (`8888:3`) are given; the search then runs until all are filled.
     #[arg(long, default_value_t = 1)]
     pub count: usize,
+}
 
+/// How candidate keys are generated, shared by `search` and `estimate`.
+#[derive(Args, Debug)]
+pub struct KeyArgs {
     /// Number of CPU threads to use. Defaults to all available cores.
     #[arg(long)]
     pub threads: Option<usize>,
 
+    /// How candidate keys are generated. `walk` picks a random key per thread and
+    /// steps through its successors with cheap point additions, which is much faster.
+    /// `mnemonic` generates BIP39 seed phrases and derives keys along the chain's HD
+    /// path (m/44'/195'/0'/0/i for Tron), so hits come with a seed phrase.
+    #[arg(long, value_enum, default_value_t = KeyStrategy::Random)]
+    pub strategy: KeyStrategy,
+
+    /// Words per seed phrase for `--strategy mnemonic`.
+    #[arg(
+        long,
+        default_value = "12",
+        value_parser = PossibleValuesParser::new(["12", "15", "18", "21", "24"])
+            .map(|s| s.parse::<usize>().unwrap())
+    )]
+    pub mnemonic_words: usize,
+
+    /// Address indices (the `i` in the HD path) checked per seed phrase for
+    /// `--strategy mnemonic`. Higher is faster, since deriving the seed dominates.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub mnemonic_indices: u32,
+}
+
+/// Options of `search`.
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    #[command(flatten)]
+    pub targets: TargetArgs,
+
+    #[command(flatten)]
+    pub keys: KeyArgs,
+
+    /// Split-key search: the public key printed by `keygen`. Results carry an offset
+    /// instead of a private key, which only the holder of the secret can `combine`.
+    #[arg(long, value_name = "PUBLIC_KEY_HEX")]
+    pub split_key: Option<PublicKey>,
+
+    /// Write each found key as a password-protected keystore file (Web3 Secret Storage
+    /// V3, importable into TronLink, MetaMask and geth) into this directory instead
+    /// of printing the private key. The password is asked for once at startup.
+    #[arg(long, value_name = "DIR")]
+    pub keystore: Option<PathBuf>,
+
+    /// Key derivation function for --keystore files.
+    #[arg(long, value_enum, default_value_t = KeystoreKdf::Scrypt, requires = "keystore")]
+    pub keystore_kdf: KeystoreKdf,
+
+    /// Read the keystore password from the first line of this file instead of
+    /// prompting for it.
+    #[arg(long, value_name = "FILE")]
+    pub password_file: Option<PathBuf>,
+
+    /// How found wallets are printed. `json`, `ndjson` and `csv` records carry the
+    /// address, hex address, keys, matched pattern, attempts so far and a timestamp.
+    /// Progress always goes to stderr, so stdout stays machine-readable.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Also write every result to this file, in the --format chosen, as soon as it is
+    /// found. The file is created readable by the owner only and synced to disk after
+    /// each result. Existing files are refused unless --append is given.
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Add results to the end of an existing --output file instead of refusing it.
+    #[arg(long, default_value_t = false, requires = "output")]
+    pub append: bool,
+
+    /// Stop the search after this long, e.g. "90s", "45m" or "2h30m", keeping whatever
+    /// was found so far.
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub max_time: Option<Duration>,
+
+    /// Stop the search after this many keys have been checked. Workers count in
+    /// batches, so each thread may check up to one batch (1024 keys) more.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_attempts: Option<u64>,
+
     /// (Placeholder) Attempt to use GPU for acceleration.
     #[arg(long, default_value_t = false)]
     pub gpu: bool,
@@ -26,3 +179,180 @@ pub struct Cli {
     #[arg(long, default_value_t = false)]
     pub show_speed: bool,
 }
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Searches for addresses matching the given prefixes, suffixes or patterns.
+    Search(SearchArgs),
+    /// Shows how many keys a search needs and how long it should take, without
+    /// searching.
+    Estimate {
+        #[command(flatten)]
+        targets: TargetArgs,
+        #[command(flatten)]
+        keys: KeyArgs,
+        /// Keys per second to base the time on. Without it, the speed is measured
+        /// for a couple of seconds with the given key options.
+        #[arg(long, value_name = "KEYS_PER_SEC")]
+        speed: Option<f64>,
+    },
+    /// Checks that a private key yields the given address.
+    Verify {
+        /// The address the key should derive. Its chain is recognized from its start.
+        #[arg(long)]
+        address: String,
+        #[command(flatten)]
+        key: PrivateKeyArgs,
+        /// Check against this chain instead of the one recognized from the address,
+        /// e.g. `evm` for a Tron key hash written as `0x...`.
+        #[arg(long, value_enum)]
+        chain: Option<Chain>,
+    },
+    /// Prints the public key and addresses of a private key.
+    Derive {
+        #[command(flatten)]
+        key: PrivateKeyArgs,
+        /// Print only this chain's address. Without it, all address types are shown.
+        #[arg(long, value_enum)]
+        chain: Option<Chain>,
+    },
+    /// Measures each step of the search (key generation, hashing, encoding, matching)
+    /// on one core, then how many keys per second each key strategy (and the GPU, if
+    /// compiled in) checks on this machine.
+    Bench {
+        /// The chain whose addresses are derived and matched.
+        #[arg(long, value_enum, default_value_t = Chain::Tron)]
+        chain: Chain,
+        /// Number of CPU threads to use. Defaults to all available cores.
+        #[arg(long)]
+        threads: Option<usize>,
+        /// How long to run each measurement, e.g. "1s" or "1m".
+        #[arg(long, value_name = "DURATION", default_value = "3s", value_parser = humantime::parse_duration)]
+        duration: Duration,
+        /// Measure only this strategy instead of all of them.
+        #[arg(long, value_enum)]
+        strategy: Option<KeyStrategy>,
+    },
+    /// Generates a secret key to keep and the public key to hand to the searcher.
+    Keygen,
+    /// Adds the offset found by a `--split-key` search to the secret from `keygen`.
+    ///
+    /// The secret key printed by `keygen` is asked for at a hidden prompt, so it
+    /// never shows up in the shell history or the process list.
+    Combine {
+        /// Read the secret key from the first line of this file instead of prompting.
+        #[arg(long, value_name = "FILE")]
+        secret_file: Option<PathBuf>,
+        /// The offset reported by the search, in hex.
+        #[arg(long, value_name = "HEX")]
+        offset: SecretKey,
+        /// The chain the search ran for, to print the resulting address.
+        #[arg(long, value_enum, default_value_t = Chain::Tron)]
+        chain: Chain,
+    },
+    /// Decrypts a keystore file and shows the address its key derives.
+    Decrypt {
+        /// The keystore file to check.
+        file: PathBuf,
+        /// Read the password from the first line of this file instead of prompting.
+        #[arg(long, value_name = "FILE")]
+        password_file: Option<PathBuf>,
+        /// The chain to show the address for.
+        #[arg(long, value_enum, default_value_t = Chain::Tron)]
+        chain: Chain,
+        /// Also print the decrypted private key.
+        #[arg(long, default_value_t = false)]
+        show_private_key: bool,
+    },
+}
+
+/// A private key given on the command line or typed at a prompt.
+#[derive(Args, Debug)]
+pub struct PrivateKeyArgs {
+    /// The private key, in hex or as a Bitcoin WIF. Without it, the key is asked for
+    /// at a hidden prompt, which keeps it out of the shell history and process list.
+    #[arg(long, value_name = "KEY")]
+    pub private_key: Option<String>,
+}
+
+impl TargetArgs {
+    /// Translates the targets into a [`SearchBuilder`], splitting off per-pattern
+    /// quotas (`8888:3`). The builder checks the rest.
+    pub fn builder(&self) -> Result<SearchBuilder, String> {
+        let mut builder = SearchBuilder::new(self.chain)
+            .ignore_case(self.ignore_case)
+            .eip55(self.eip55)
+            .count(self.count);
+        for entry in &self.prefixes {
+            let (prefix, quota) = split_quota(entry)?;
+            builder = builder.prefix_with_quota(prefix, quota);
+        }
+        for entry in &self.suffixes {
+            let (suffix, quota) = split_quota(entry)?;
+            builder = builder.suffix_with_quota(suffix, quota);
+        }
+        for entry in &self.contains {
+            let (entry, quota) = split_quota(entry)?;
+            let (text, positions) = split_positions(&entry)?;
+            builder = builder.target(Target {
+                quota,
+                positions,
+                ..Target::new(TargetKind::Contains, text)
+            });
+        }
+        for entry in &self.patterns {
+            let (pattern, quota) = split_quota(entry)?;
+            builder = builder.pattern_with_quota(pattern, quota);
+        }
+        if self.score {
+            builder = builder.score(BeautyScore, self.top);
+        }
+        if let Some(path) = &self.patterns_file {
+            for target in patterns_file::load(path)? {
+                builder = builder.target(target);
+            }
+        }
+        Ok(builder)
+    }
+}
+
+impl KeyArgs {
+    /// Sets the key generation options on `builder`.
+    pub fn apply(&self, mut builder: SearchBuilder) -> SearchBuilder {
+        builder = builder
+            .strategy(self.strategy)
+            .mnemonic_words(self.mnemonic_words)
+            .mnemonic_indices(self.mnemonic_indices);
+        if let Some(threads) = self.threads {
+            builder = builder.threads(threads);
+        }
+        builder
+    }
+}
+
+impl SearchArgs {
+    /// Translates the search options into a [`SearchBuilder`].
+    pub fn search_builder(&self) -> Result<SearchBuilder, String> {
+        if self.keys.strategy == KeyStrategy::Mnemonic && self.keystore.is_some() {
+            return Err(
+                "--strategy mnemonic prints the seed phrase, which makes --keystore pointless; use one or the other"
+                    .to_string(),
+            );
+        }
+        let mut builder = self
+            .keys
+            .apply(self.targets.builder()?)
+            // Without the feature, main.rs warns and falls back to the CPU.
+            .gpu(self.gpu && cfg!(feature = "gpu"));
+        if let Some(base) = self.split_key {
+            builder = builder.split_key(base);
+        }
+        if let Some(max_time) = self.max_time {
+            builder = builder.max_time(max_time);
+        }
+        if let Some(max_attempts) = self.max_attempts {
+            builder = builder.max_attempts(max_attempts);
+        }
+        Ok(builder)
+    }
+}
@@ -0,0 +1,186 @@
+//! src/contains.rs
+
+use aho_corasick::AhoCorasick;
+use std::fmt;
+use std::ops::Range;
+
+/// A substring wanted anywhere in the address after the lead, or only starting
+/// within `positions`, counted from the first character after the lead (0).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Contains {
+    pub text: String,
+    pub positions: Range<usize>,
+}
+
+impl Contains {
+    pub fn new(text: impl Into<String>, positions: Option<Range<usize>>) -> Self {
+        Self {
+            text: text.into(),
+            positions: positions.unwrap_or(0..usize::MAX),
+        }
+    }
+
+    /// Where the substring can start in an address body (the address without its
+    /// lead) of `len` characters; empty if it never fits.
+    pub fn starts(&self, len: usize) -> Range<usize> {
+        let last = (len + 1).saturating_sub(self.text.len());
+        self.positions.start..self.positions.end.min(last)
+    }
+}
+
+/// Substrings searched for all at once with an Aho-Corasick automaton: an address
+/// costs one pass over its characters however many substrings there are, and only
+/// the occurrences found are checked for their position and letter case.
+pub(crate) struct ContainsSet {
+    automaton: AhoCorasick,
+    targets: Vec<Contains>,
+    /// Per target, whether it matches regardless of case. The automaton folds case
+    /// if any target does, and exact targets are compared again on a hit.
+    ignore_case: Vec<bool>,
+}
+
+impl ContainsSet {
+    /// Builds the automaton over `targets`, each matched regardless of case if
+    /// `ignore_case` says so for its index.
+    pub(crate) fn new(targets: &[Contains], ignore_case: impl Fn(usize) -> bool) -> Self {
+        let ignore_case: Vec<bool> = (0..targets.len()).map(ignore_case).collect();
+        let automaton = AhoCorasick::builder()
+            .ascii_case_insensitive(ignore_case.iter().any(|&folds| folds))
+            .build(targets.iter().map(|target| &target.text))
+            .expect("substrings were validated");
+        Self {
+            automaton,
+            targets: targets.to_vec(),
+            ignore_case,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Finds the first target given that an address body (the address without its
+    /// lead) contains at an allowed position, skipping targets for which `skip`
+    /// returns true.
+    #[inline]
+    pub(crate) fn find(&self, body: &[u8], skip: impl Fn(usize) -> bool) -> Option<usize> {
+        self.automaton
+            .find_overlapping_iter(body)
+            .filter_map(|hit| {
+                let index = hit.pattern().as_usize();
+                let target = &self.targets[index];
+                let placed = target.starts(body.len()).contains(&hit.start());
+                let cased = self.ignore_case[index] || &body[hit.range()] == target.text.as_bytes();
+                (placed && cased && !skip(index)).then_some(index)
+            })
+            .min()
+    }
+}
+
+/// Written the way it is given on the command line, e.g. `COOL@3..10`.
+impl fmt::Display for Contains {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.positions.start, self.positions.end) {
+            (0, usize::MAX) => write!(f, "{}", self.text),
+            (start, usize::MAX) => write!(f, "{}@{}..", self.text, start),
+            (start, end) if end == start + 1 => write!(f, "{}@{}", self.text, start),
+            (start, end) => write!(f, "{}@{}..{}", self.text, start, end),
+        }
+    }
+}
+
+/// Splits an optional position range off a command-line entry: `COOL@3..10` starts
+/// at positions 3 to 9, `COOL@3..` anywhere from 3 on, `COOL@..10` before 10 and
+/// `COOL@3` at 3 exactly.
+pub fn split_positions(entry: &str) -> Result<(String, Option<Range<usize>>), String> {
+    let Some((text, positions)) = entry.rsplit_once('@') else {
+        return Ok((entry.to_string(), None));
+    };
+    let invalid = || format!("Invalid position range in \"{}\"", entry);
+    let number = |s: &str| s.parse::<usize>().map_err(|_| invalid());
+    let range = match positions.split_once("..") {
+        Some((start, end)) => {
+            let start = if start.is_empty() { 0 } else { number(start)? };
+            let end = if end.is_empty() {
+                usize::MAX
+            } else {
+                number(end)?
+            };
+            start..end
+        }
+        None => {
+            let start = number(positions)?;
+            start..start.checked_add(1).ok_or_else(invalid)?
+        }
+    };
+    if text.is_empty() || range.is_empty() {
+        return Err(invalid());
+    }
+    Ok((text.to_string(), Some(range)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        assert_eq!(split_positions("COOL"), Ok(("COOL".to_string(), None)));
+        assert_eq!(
+            split_positions("COOL@3..10"),
+            Ok(("COOL".to_string(), Some(3..10)))
+        );
+        assert_eq!(
+            split_positions("COOL@..10"),
+            Ok(("COOL".to_string(), Some(0..10)))
+        );
+        assert_eq!(
+            split_positions("COOL@3"),
+            Ok(("COOL".to_string(), Some(3..4)))
+        );
+        assert!(split_positions("COOL@5..5").is_err());
+        assert!(split_positions("COOL@x").is_err());
+        assert!(split_positions(&format!("COOL@{}", usize::MAX)).is_err());
+
+        for entry in ["COOL", "COOL@3..", "COOL@3", "COOL@3..10"] {
+            let (text, positions) = split_positions(entry).unwrap();
+            assert_eq!(Contains::new(text, positions).to_string(), entry);
+        }
+    }
+
+    #[test]
+    fn test_matching_within_positions() {
+        let body = b"RabCOOLxyz";
+        let find = |target: Contains, ignore_case| {
+            ContainsSet::new(&[target], |_| ignore_case).find(body, |_| false)
+        };
+        assert_eq!(find(Contains::new("COOL", None), false), Some(0));
+        assert_eq!(find(Contains::new("COOL", Some(3..4)), false), Some(0));
+        assert_eq!(find(Contains::new("COOL", Some(4..10)), false), None);
+        assert_eq!(find(Contains::new("COOL", Some(0..3)), false), None);
+        assert_eq!(find(Contains::new("cool", Some(0..4)), true), Some(0));
+        assert_eq!(find(Contains::new("cool", None), false), None);
+        // Ranges reaching past the end only count positions where it fits.
+        assert_eq!(Contains::new("xyz", Some(5..20)).starts(body.len()), 5..8);
+        assert_eq!(find(Contains::new("xyz", Some(5..20)), false), Some(0));
+    }
+
+    #[test]
+    fn test_many_substrings_with_their_own_case() {
+        let mut targets: Vec<Contains> = (0..5_000)
+            .map(|i| Contains::new(format!("N{:04}", i), None))
+            .collect();
+        targets.push(Contains::new("Ab", None));
+        targets.push(Contains::new("ab", Some(0..1)));
+        // Only the even ones, and `ab`, ignore case.
+        let set = ContainsSet::new(&targets, |i| i % 2 == 0 || i == 5_001);
+        assert_eq!(set.find(b"xxn0042xx", |_| false), Some(42));
+        assert_eq!(set.find(b"xxn0043xx", |_| false), None);
+        assert_eq!(set.find(b"xxN0043xx", |_| false), Some(43));
+        // The first target given wins, and filled ones are skipped.
+        assert_eq!(set.find(b"AbN0042", |_| false), Some(42));
+        assert_eq!(set.find(b"AbN0042", |i| i == 42), Some(5_000));
+        assert_eq!(set.find(b"AbN0042", |i| i < 5_001), Some(5_001));
+        assert_eq!(set.find(b"xAb", |i| i == 5_000), None);
+    }
+}
@@ -0,0 +1,663 @@
+//! src/estimate.rs
+
+use crate::address::BASE58_ALPHABET;
+use crate::chain::Chain;
+use crate::contains::Contains;
+use crate::worker::SearchTargets;
+
+/// Probability that a character drawn uniformly from `pool` matches `c`.
+///
+/// With `ignore_case`, letters present in both cases (most of them) match twice as
+/// often, while digits and one-case letters like `L`, `i` and `o` do not.
+pub fn char_match_prob(c: char, pool: &[char], ignore_case: bool) -> f64 {
+    let hits = pool
+        .iter()
+        .filter(|&&p| {
+            if ignore_case {
+                p.eq_ignore_ascii_case(&c)
+            } else {
+                p == c
+            }
+        })
+        .count();
+    hits as f64 / pool.len() as f64
+}
+
+/// A prefix or suffix, and whether it matches regardless of letter case.
+type Affix<'a> = (&'a str, bool);
+
+/// Above this many letter-case spellings, a case-insensitive Base58 prefix is
+/// estimated per character instead of spelling by spelling.
+const MAX_SPELLINGS: usize = 4096;
+
+/// The odds of a search: how many keys it takes to fill it, on average and with a
+/// given confidence.
+///
+/// Each key is a Bernoulli trial, so the attempts until the `n`-th result follow a
+/// negative binomial distribution, which for the tiny odds of a vanity address is a
+/// Gamma distribution. With per-target quotas every target is an independent
+/// stream of results, and the search takes as long as its slowest stream.
+///
+/// Prefixes, suffixes and substrings are estimated; regex and glob patterns are not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    streams: Vec<Stream>,
+}
+
+/// Results that come from one target (or the union of all of them).
+#[derive(Debug, Clone, PartialEq)]
+struct Stream {
+    /// Probability that a single key is a result.
+    probability: f64,
+    /// Results wanted.
+    wanted: usize,
+}
+
+impl Estimate {
+    /// Estimates a search for `count` results, or for the per-target quotas if any
+    /// are set. Returns `None` without prefixes, suffixes and substrings.
+    pub fn new(targets: &SearchTargets, count: usize) -> Option<Self> {
+        if targets.prefixes.is_empty() && targets.suffixes.is_empty() && targets.contains.is_empty()
+        {
+            return None;
+        }
+        let prefixes: Vec<Affix> = targets
+            .prefixes
+            .iter()
+            .enumerate()
+            .map(|(i, prefix)| (prefix.as_str(), targets.prefix_ignores_case(i)))
+            .collect();
+        let suffixes: Vec<Affix> = targets
+            .suffixes
+            .iter()
+            .enumerate()
+            .map(|(i, suffix)| (suffix.as_str(), targets.suffix_ignores_case(i)))
+            .collect();
+        let narrowing: Vec<Affix> = targets
+            .narrowing_prefixes
+            .iter()
+            .map(|prefix| (prefix.as_str(), targets.ignore_case))
+            .collect();
+        let (narrowed, standalone) = suffixes.split_at(targets.narrowed_suffixes);
+
+        let streams = if targets.quotas.iter().any(Option::is_some) {
+            // One stream per target, in the order of `SearchTargets::quotas`.
+            // Substrings follow, and patterns are left out.
+            let prefix_streams = prefixes
+                .iter()
+                .map(|prefix| prefix_probability(targets, std::slice::from_ref(prefix)));
+            let suffix_streams = suffixes.iter().enumerate().map(|(i, suffix)| {
+                let suffix = std::slice::from_ref(suffix);
+                if i < narrowed.len() {
+                    combined_probability(targets, &narrowing, suffix)
+                } else {
+                    suffix_probability(targets, suffix)
+                }
+            });
+            let affix_streams = prefix_streams
+                .chain(suffix_streams)
+                .zip(&targets.quotas)
+                .map(|(probability, quota)| Stream {
+                    probability,
+                    wanted: quota.unwrap_or(1),
+                });
+            let contains_streams = targets
+                .contains
+                .iter()
+                .enumerate()
+                .zip(&targets.quotas[prefixes.len() + suffixes.len()..])
+                .map(|((i, within), quota)| Stream {
+                    probability: contains_probability(
+                        targets,
+                        within,
+                        targets.contains_ignores_case(i),
+                    ),
+                    wanted: quota.unwrap_or(1),
+                });
+            affix_streams.chain(contains_streams).collect()
+        } else {
+            // Prefixes and suffixes that are targets of their own are added up like
+            // substrings below.
+            let mut affix_prob = 0.0;
+            if !prefixes.is_empty() {
+                affix_prob += prefix_probability(targets, &prefixes);
+            }
+            if !narrowed.is_empty() {
+                affix_prob += combined_probability(targets, &narrowing, narrowed);
+            }
+            if !standalone.is_empty() {
+                affix_prob += suffix_probability(targets, standalone);
+            }
+            // Substrings overlapping each other are added up as if they were not,
+            // which overstates the odds by little: the longer one is much rarer.
+            let contains_prob: f64 = targets
+                .contains
+                .iter()
+                .enumerate()
+                .map(|(i, within)| {
+                    contains_probability(targets, within, targets.contains_ignores_case(i))
+                })
+                .sum();
+            vec![Stream {
+                probability: (affix_prob + contains_prob).min(1.0),
+                wanted: count,
+            }]
+        };
+        Some(Self { streams })
+    }
+
+    /// Probability that a single key is a result; "1 in N" with N its inverse.
+    pub fn probability(&self) -> f64 {
+        self.streams.iter().map(|s| s.probability).sum()
+    }
+
+    /// Results the estimate covers.
+    pub fn wanted(&self) -> usize {
+        self.streams.iter().map(|s| s.wanted).sum()
+    }
+
+    /// Mean number of attempts to fill the search.
+    pub fn expected_attempts(&self) -> f64 {
+        if let [stream] = self.streams.as_slice() {
+            return stream.wanted as f64 / stream.probability;
+        }
+        if self.streams.iter().any(|s| s.probability <= 0.0) {
+            return f64::INFINITY;
+        }
+        // The mean of the slowest stream: the integral of its survival function,
+        // which is negligible beyond the one-in-a-billion point.
+        const STEPS: usize = 4096;
+        let end = self.attempts_for(1.0 - 1e-9);
+        let step = end / STEPS as f64;
+        let survival = |i: usize| 1.0 - self.success_probability(i as f64 * step);
+        // Simpson's rule.
+        let inner: f64 = (1..STEPS)
+            .map(|i| survival(i) * if i % 2 == 1 { 4.0 } else { 2.0 })
+            .sum();
+        (survival(0) + inner + survival(STEPS)) * step / 3.0
+    }
+
+    /// Probability that the search is filled within `attempts` keys.
+    pub fn success_probability(&self, attempts: f64) -> f64 {
+        self.streams
+            .iter()
+            .map(|s| gamma_cdf(s.wanted, s.probability * attempts))
+            .product()
+    }
+
+    /// Number of keys after which the search is filled with probability
+    /// `confidence` (e.g. 0.9 for "nine in ten searches are done by then").
+    pub fn attempts_for(&self, confidence: f64) -> f64 {
+        if self.streams.iter().any(|s| s.probability <= 0.0) {
+            return f64::INFINITY;
+        }
+        let mut high = self
+            .streams
+            .iter()
+            .map(|s| 1.0 / s.probability)
+            .sum::<f64>();
+        while self.success_probability(high) < confidence {
+            high *= 2.0;
+        }
+        let mut low = 0.0;
+        for _ in 0..100 {
+            let mid = (low + high) / 2.0;
+            if self.success_probability(mid) < confidence {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        high
+    }
+}
+
+/// Probability that the `n`-th event of a unit-rate Poisson process has happened by
+/// time `x`, i.e. that a Poisson variable with mean `x` is at least `n`.
+fn gamma_cdf(n: usize, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    // 1 - sum_{k<n} e^-x x^k / k!, with the terms in log space so large `x` does
+    // not underflow `e^-x` before it meets `x^k`.
+    let mut log_term = -x;
+    let mut below = 0.0;
+    for k in 0..n {
+        if k > 0 {
+            log_term += x.ln() - (k as f64).ln();
+        }
+        below += log_term.exp();
+    }
+    (1.0 - below).clamp(0.0, 1.0)
+}
+
+/// Letter case is folded away where it does not matter for matching.
+fn canonical(target: &str, ignore_case: bool) -> String {
+    if ignore_case {
+        target.to_ascii_lowercase()
+    } else {
+        target.to_string()
+    }
+}
+
+/// Drops targets implied by another one: every address ending in `888` also ends in
+/// `88`, so `88,888` is as likely as `88` alone, and `COOL` adds nothing to `cool`
+/// in any case. What remains are disjoint events.
+fn independent_targets(targets: &[Affix], prefixes: bool) -> Vec<(String, bool)> {
+    let mut canonical: Vec<(String, bool)> = targets
+        .iter()
+        .map(|&(target, ignore_case)| (canonical(target, ignore_case), ignore_case))
+        .collect();
+    // Case-insensitive targets first among those of a length, as they imply the
+    // exact ones.
+    canonical.sort_by_key(|(target, ignore_case)| (target.len(), !ignore_case));
+    canonical.dedup();
+    let mut kept: Vec<(String, bool)> = Vec::new();
+    for (target, ignore_case) in canonical {
+        let implied = kept.iter().any(|(shorter, shorter_ignores_case)| {
+            let target = match (shorter_ignores_case, ignore_case) {
+                (true, _) => target.to_ascii_lowercase(),
+                (false, false) => target.clone(),
+                (false, true) => return false,
+            };
+            if prefixes {
+                target.starts_with(shorter.as_str())
+            } else {
+                target.ends_with(shorter.as_str())
+            }
+        });
+        if !implied {
+            kept.push((target, ignore_case));
+        }
+    }
+    kept
+}
+
+/// Probability that an address ends in any of `suffixes`. Characters near the end
+/// of an address are uniformly distributed for every chain.
+fn suffix_probability(targets: &SearchTargets, suffixes: &[Affix]) -> f64 {
+    let pool = targets.chain.char_pool(targets.eip55);
+    independent_targets(suffixes, false)
+        .iter()
+        .map(|(suffix, ignore_case)| {
+            suffix
+                .chars()
+                .map(|c| char_match_prob(c, &pool, *ignore_case))
+                .product::<f64>()
+        })
+        .sum()
+}
+
+/// Probability that an address starts (after its lead) with any of `prefixes`.
+fn prefix_probability(targets: &SearchTargets, prefixes: &[Affix]) -> f64 {
+    independent_targets(prefixes, true)
+        .iter()
+        .map(|(prefix, ignore_case)| {
+            let spellings = spellings(targets.chain, prefix, *ignore_case);
+            match (base58_number_range(targets.chain), spellings) {
+                (Some(range), Some(spellings)) => spellings
+                    .iter()
+                    .map(|spelling| range.prefix_probability(spelling))
+                    .sum(),
+                _ => per_char_prefix_probability(targets, prefix, *ignore_case),
+            }
+        })
+        .sum::<f64>()
+        .min(1.0)
+}
+
+/// Probability that an address starts with one of `prefixes` and ends with one of
+/// `suffixes`, counting only pairs that fit side by side. Suffixes are grouped by
+/// the prefixes that fit next to them, usually all of them.
+fn combined_probability(targets: &SearchTargets, prefixes: &[Affix], suffixes: &[Affix]) -> f64 {
+    let room = targets.chain.address_len() - targets.chain.lead().len();
+    let mut groups: Vec<(Vec<Affix>, Vec<Affix>)> = Vec::new();
+    for &suffix in suffixes {
+        let fitting: Vec<Affix> = prefixes
+            .iter()
+            .copied()
+            .filter(|(prefix, _)| prefix.len() + suffix.0.len() <= room)
+            .collect();
+        match groups.iter_mut().find(|(group, _)| *group == fitting) {
+            Some((_, group)) => group.push(suffix),
+            None => groups.push((fitting, vec![suffix])),
+        }
+    }
+    groups
+        .iter()
+        .filter(|(fitting, _)| !fitting.is_empty())
+        .map(|(fitting, group)| {
+            prefix_probability(targets, fitting) * suffix_probability(targets, group)
+        })
+        .sum::<f64>()
+        .min(1.0)
+}
+
+/// Probability that an address contains a substring at one of its allowed starts.
+///
+/// A start right after the lead is weighed like a prefix, since the first
+/// characters are skewed (Tron's is `9`..`Z`); later characters are close to
+/// uniform. A self-overlapping substring like `8888` shows up in clumps (`88888`
+/// holds two), so a start only counts when it does not continue an occurrence one
+/// period earlier, and the clumps are taken to be independent.
+pub(crate) fn contains_probability(
+    targets: &SearchTargets,
+    within: &Contains,
+    ignore_case: bool,
+) -> f64 {
+    let chain = targets.chain;
+    let pool = chain.char_pool(targets.eip55);
+    let text = canonical(&within.text, ignore_case);
+    let odds = |chars: &str| -> f64 {
+        chars
+            .chars()
+            .map(|c| char_match_prob(c, &pool, ignore_case))
+            .product()
+    };
+    let anywhere = odds(&text);
+    let period = (1..text.len())
+        .find(|&d| text[d..] == text[..text.len() - d])
+        .unwrap_or(text.len());
+    let continues_earlier = if period < text.len() {
+        odds(&text[..period])
+    } else {
+        0.0
+    };
+
+    let starts = within.starts(chain.address_len() - chain.lead().len());
+    let first = starts.start;
+    // Summed as logarithms: for a long substring `1 - here` rounds to exactly 1.
+    let log_misses: f64 = starts
+        .map(|start| {
+            let here = if start == 0 {
+                prefix_probability(targets, &[(&within.text, ignore_case)])
+            } else {
+                anywhere
+            };
+            if start >= first + period {
+                (-here * (1.0 - continues_earlier)).ln_1p()
+            } else {
+                (-here).ln_1p()
+            }
+        })
+        .sum();
+    -log_misses.exp_m1()
+}
+
+/// Treats the characters after the first as uniform, which is exact for hex and
+/// bech32 and close for Base58.
+fn per_char_prefix_probability(targets: &SearchTargets, prefix: &str, ignore_case: bool) -> f64 {
+    let alphabet = targets.chain.char_pool(targets.eip55);
+    let first_chars = targets.chain.first_char_pool(targets.eip55);
+    prefix
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let pool = if i == 0 { &first_chars } else { &alphabet };
+            char_match_prob(c, pool, ignore_case)
+        })
+        .product()
+}
+
+/// Every spelling of `target` in the chain's alphabet; only one without
+/// `ignore_case`. `None` if there are too many to add up one by one.
+fn spellings(chain: Chain, target: &str, ignore_case: bool) -> Option<Vec<String>> {
+    let mut spellings = vec![String::new()];
+    for c in target.chars() {
+        let variants: Vec<char> = chain
+            .alphabet()
+            .iter()
+            .map(|&a| a as char)
+            .filter(|a| {
+                if ignore_case {
+                    a.eq_ignore_ascii_case(&c)
+                } else {
+                    *a == c
+                }
+            })
+            .collect();
+        if spellings.len() * variants.len() > MAX_SPELLINGS {
+            return None;
+        }
+        spellings = spellings
+            .iter()
+            .flat_map(|s| variants.iter().map(move |v| format!("{}{}", s, v)))
+            .collect();
+    }
+    Some(spellings)
+}
+
+/// Base58 addresses are a 25-byte number written in base 58, so their leading
+/// characters are far from uniform: a Tron address body is a number in
+/// `0x41 · 2^192 .. 0x42 · 2^192` (which is why it starts with `T`), a legacy
+/// Bitcoin body one below `2^192`, with each leading zero byte written as a `1`.
+struct Base58Range {
+    /// Digits of the lead that are part of the number (the `T` of Tron).
+    lead: Vec<u8>,
+    low: f64,
+    high: f64,
+    /// Bitcoin: leading `1`s stand for zero bytes rather than digits.
+    zero_bytes_as_ones: bool,
+}
+
+fn base58_number_range(chain: Chain) -> Option<Base58Range> {
+    let range = 2f64.powi(192);
+    match chain {
+        Chain::Tron => Some(Base58Range {
+            lead: vec![digit('T')],
+            low: 65.0 * range,
+            high: 66.0 * range,
+            zero_bytes_as_ones: false,
+        }),
+        Chain::Btc => Some(Base58Range {
+            lead: Vec::new(),
+            low: 0.0,
+            high: range,
+            zero_bytes_as_ones: true,
+        }),
+        _ => None,
+    }
+}
+
+fn digit(c: char) -> u8 {
+    BASE58_ALPHABET
+        .iter()
+        .position(|&a| a as char == c)
+        .expect("validated targets are Base58") as u8
+}
+
+impl Base58Range {
+    /// The share of numbers in the range whose Base58 digits start with the lead
+    /// and `prefix`.
+    fn prefix_probability(&self, prefix: &str) -> f64 {
+        let (mut low, mut high) = (self.low, self.high);
+        let mut digits = self.lead.clone();
+        let mut chars = prefix.chars().peekable();
+        if self.zero_bytes_as_ones {
+            // Each further `1` is a zero byte: the number is 256 times smaller, but
+            // not so small that the next digit would be another `1`.
+            let mut ones = 0;
+            while chars.next_if_eq(&'1').is_some() {
+                ones += 1;
+            }
+            high /= 256f64.powi(ones);
+            low = if chars.peek().is_some() {
+                high / 256.0
+            } else {
+                0.0
+            };
+        }
+        digits.extend(chars.map(digit));
+        if digits.is_empty() {
+            return (high - low) / (self.high - self.low);
+        }
+
+        // The numbers written with these leading digits, at every length: the
+        // intervals [d · 58^m, (d + 1) · 58^m).
+        let leading = digits.iter().fold(0.0, |acc, &d| acc * 58.0 + d as f64);
+        let mut covered = 0.0;
+        for m in 0..40 {
+            let width = 58f64.powi(m);
+            let (start, end) = (leading * width, (leading + 1.0) * width);
+            if start >= low && end <= high {
+                // Exact, where `end - start` would lose the width to rounding.
+                covered += width;
+            } else if start < high && end > low {
+                covered += end.min(high) - start.max(low);
+            }
+        }
+        covered / (self.high - self.low)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::CaseOverrides;
+
+    /// Prefixes and suffixes as `--prefixes` and `--suffixes` give them.
+    fn targets(chain: Chain, prefixes: &[&str], suffixes: &[&str]) -> SearchTargets {
+        let strings = |affixes: &[&str]| affixes.iter().map(|s| s.to_string()).collect();
+        let (prefixes, narrowing_prefixes) = if suffixes.is_empty() {
+            (strings(prefixes), Vec::new())
+        } else {
+            (Vec::new(), strings(prefixes))
+        };
+        let narrowed_suffixes = if narrowing_prefixes.is_empty() {
+            0
+        } else {
+            suffixes.len()
+        };
+        SearchTargets {
+            prefixes,
+            suffixes: strings(suffixes),
+            narrowing_prefixes,
+            narrowed_suffixes,
+            contains: Vec::new(),
+            patterns: Vec::new(),
+            ignore_case: false,
+            case_overrides: CaseOverrides::default(),
+            chain,
+            eip55: false,
+            quotas: Vec::new(),
+            split_key: None,
+            score: None,
+        }
+    }
+
+    #[test]
+    fn test_first_characters_add_up() {
+        for chain in [Chain::Tron, Chain::Btc] {
+            let range = base58_number_range(chain).unwrap();
+            let total: f64 = BASE58_ALPHABET
+                .iter()
+                .map(|&c| range.prefix_probability(&(c as char).to_string()))
+                .sum();
+            assert!((total - 1.0).abs() < 1e-9, "{:?}: {}", chain, total);
+        }
+        // Tron continues with `9`..`Z` only, and not evenly: `9` and `Z` are rare.
+        let tron = base58_number_range(Chain::Tron).unwrap();
+        assert_eq!(tron.prefix_probability("a"), 0.0);
+        assert!(tron.prefix_probability("9") < tron.prefix_probability("R") / 10.0);
+        assert!(tron.prefix_probability("Z") < tron.prefix_probability("R") / 2.0);
+        // One in 256 legacy addresses has a second zero byte, `11...`.
+        let btc = base58_number_range(Chain::Btc).unwrap();
+        assert!((btc.prefix_probability("1") - 1.0 / 256.0).abs() < 1e-12);
+        // The following digits are close to uniform.
+        let two = tron.prefix_probability("R");
+        assert!((tron.prefix_probability("Rx") / two - 1.0 / 58.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_overlapping_and_case_insensitive_targets() {
+        let one = Estimate::new(&targets(Chain::Tron, &[], &["88"]), 1).unwrap();
+        let nested = Estimate::new(&targets(Chain::Tron, &[], &["88", "888", "88"]), 1).unwrap();
+        assert_eq!(one.probability(), nested.probability());
+        assert_eq!(one.probability(), 1.0 / 58.0 / 58.0);
+
+        let disjoint = Estimate::new(&targets(Chain::Tron, &[], &["88", "99"]), 1).unwrap();
+        assert_eq!(disjoint.probability(), 2.0 * one.probability());
+
+        let mut any_case = targets(Chain::Tron, &["Rx"], &["ab"]);
+        any_case.ignore_case = true;
+        let any_case = Estimate::new(&any_case, 1).unwrap();
+        let exact = Estimate::new(&targets(Chain::Tron, &["Rx"], &["ab"]), 1).unwrap();
+        // No Tron address continues with a lowercase `r`, so the prefix only gains
+        // from its `x`.
+        assert!((any_case.probability() / exact.probability() - 8.0).abs() < 1e-9);
+
+        // A suffix that ignores case covers the exact spellings of the same word.
+        let mut mixed = targets(Chain::Tron, &[], &["AB", "ab", "xAB"]);
+        mixed.case_overrides.suffixes = vec![1];
+        let mixed = Estimate::new(&mixed, 1).unwrap();
+        let mut folded_targets = targets(Chain::Tron, &[], &["ab"]);
+        folded_targets.ignore_case = true;
+        let folded = Estimate::new(&folded_targets, 1).unwrap();
+        assert_eq!(mixed.probability(), folded.probability());
+    }
+
+    #[test]
+    fn test_prefix_and_suffix_pairs_that_do_not_fit() {
+        let long_prefix = "R".repeat(21);
+        let long_suffix = "a".repeat(15);
+        let estimate = |prefixes: &[&str], suffixes: &[&str]| {
+            Estimate::new(&targets(Chain::Tron, prefixes, suffixes), 1)
+                .unwrap()
+                .probability()
+        };
+        // The long suffix only counts next to the short prefix.
+        let mixed = estimate(&["RX", &long_prefix], &["a", &long_suffix]);
+        let expected = estimate(&["RX", &long_prefix], &["a"]) + estimate(&["RX"], &[&long_suffix]);
+        assert!((mixed / expected - 1.0).abs() < 1e-9);
+        assert_eq!(estimate(&[&long_prefix], &[&long_suffix]), 0.0);
+    }
+
+    #[test]
+    fn test_substring_positions() {
+        let tron = targets(Chain::Tron, &[], &[]);
+        let anywhere = contains_probability(&tron, &Contains::new("kiwi", None), false);
+        let suffix = Estimate::new(&targets(Chain::Tron, &[], &["kiwi"]), 1).unwrap();
+        // One chance at each start where four characters fit, but the first: no
+        // Tron address continues its `T` with a lowercase letter.
+        assert!((anywhere / suffix.probability() - 29.0).abs() < 0.1);
+
+        // Right after the lead, the substring follows the prefix odds.
+        let at = |text: &str, start| {
+            contains_probability(&tron, &Contains::new(text, Some(start..start + 1)), false)
+        };
+        let prefix = Estimate::new(&targets(Chain::Tron, &["R"], &[]), 1).unwrap();
+        assert!((at("R", 0) - prefix.probability()).abs() < 1e-12);
+        assert_eq!(at("a", 0), 0.0);
+        assert!((at("a", 1) - 1.0 / 58.0).abs() < 1e-12);
+
+        // A run overlaps itself, so its occurrences come in clumps.
+        let run = contains_probability(&tron, &Contains::new("8888", None), false);
+        assert!(run < anywhere);
+
+        // Far below the precision of `1 - p`, but still possible.
+        let long = contains_probability(&tron, &Contains::new("zzzzzzzzzz", None), false);
+        assert!(long > 0.0 && long < 1e-15);
+    }
+
+    #[test]
+    fn test_completion_curve() {
+        let single = Estimate::new(&targets(Chain::Evm, &[], &["abc"]), 1).unwrap();
+        let p = single.probability();
+        assert_eq!(single.expected_attempts(), 4096.0);
+        assert!((single.attempts_for(0.5) * p - 2f64.ln()).abs() < 1e-9);
+        assert!((single.attempts_for(0.99) * p - 100f64.ln()).abs() < 1e-9);
+        assert!((single.success_probability(4096.0) - (1.0 - (-1f64).exp())).abs() < 1e-9);
+
+        let three = Estimate::new(&targets(Chain::Evm, &[], &["abc"]), 3).unwrap();
+        assert_eq!(three.expected_attempts(), 3.0 * 4096.0);
+        assert!(three.attempts_for(0.5) < three.expected_attempts());
+
+        // Two targets with a quota each take as long as the slower one: 1.5x the
+        // mean of one.
+        let mut quotas = targets(Chain::Evm, &[], &["abc", "def"]);
+        quotas.quotas = vec![Some(1), None];
+        let quotas = Estimate::new(&quotas, 1).unwrap();
+        assert_eq!(quotas.wanted(), 2);
+        assert!((quotas.expected_attempts() / (1.5 * 4096.0) - 1.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,87 @@
+//! src/evm.rs
+
+use tiny_keccak::{Hasher, Keccak};
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    keccak.update(data);
+    let mut hash = [0u8; 32];
+    keccak.finalize(&mut hash);
+    hash
+}
+
+/// Derives the 20-byte EVM address from an uncompressed SEC1 public key.
+///
+/// This is the same Keccak-256 derivation Tron uses; only the encoding differs.
+pub fn evm_address_bytes(pk_uncompressed: &[u8; 65]) -> [u8; 20] {
+    let hash = keccak256(&pk_uncompressed[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/// Formats an address as `0x` followed by lowercase hex.
+pub fn to_lower_address(address: &[u8; 20]) -> String {
+    format!("0x{}", hex::encode(address))
+}
+
+/// Formats an address with the EIP-55 mixed-case checksum.
+///
+/// Each hex letter is uppercased when the matching nibble of the Keccak-256 hash of
+/// the lowercase hex string is 8 or higher.
+pub fn to_checksum_address(address: &[u8; 20]) -> String {
+    let lower = hex::encode(address);
+    let hash = keccak256(lower.as_bytes());
+
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        if nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+    checksummed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Secp256k1, SecretKey};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_known_private_key_to_address() {
+        let secp = Secp256k1::new();
+        let secret_key =
+            SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap();
+        let address = evm_address_bytes(&secret_key.public_key(&secp).serialize_uncompressed());
+
+        assert_eq!(
+            to_lower_address(&address),
+            "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+        );
+        assert_eq!(
+            to_checksum_address(&address),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+    }
+
+    #[test]
+    fn test_eip55_vectors() {
+        // Examples from the EIP-55 specification.
+        for expected in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let mut address = [0u8; 20];
+            hex::decode_to_slice(&expected[2..], &mut address).unwrap();
+            assert_eq!(to_checksum_address(&address), expected);
+        }
+    }
+}
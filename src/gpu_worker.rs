@@ -1,9 +1,9 @@
 //! src/gpu_worker.rs
 
-use crate::address::public_key_to_tron_address;
-use crate::worker::FoundWallet;
+use crate::secret::SecretBytes;
+use crate::worker::{Attempts, FoundWallet, Matcher, SearchTargets};
 use bytemuck::{Pod, Zeroable};
-use secp256k1::{PublicKey, Secp256k1};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
 use std::time::Instant;
@@ -20,13 +20,37 @@ struct GpuPublicKey {
     data: [u8; 64],
 }
 
+/// The keypairs of one round, erased before the vector is reused and when the
+/// search ends, like the keys of the CPU workers.
+struct KeyBatch(Vec<(SecretKey, PublicKey)>);
+
+impl KeyBatch {
+    fn clear(&mut self) {
+        for (secret, _) in &mut self.0 {
+            secret.non_secure_erase();
+        }
+        self.0.clear();
+    }
+}
+
+impl Drop for KeyBatch {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
 /// Main async function to drive the GPU-based search.
+///
+/// Like the CPU search, it streams every match through `sender` and keeps going
+/// until `should_stop` is set.
 pub async fn search(
-    suffixes: Vec<String>,
+    targets: SearchTargets,
     sender: Sender<FoundWallet>,
     should_stop: &AtomicBool,
+    filled: &[AtomicBool],
+    attempts: &Attempts,
 ) {
-    println!("[GPU] Initializing GPU device...");
+    eprintln!("[GPU] Initializing GPU device...");
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
     let adapter = instance
         .request_adapter(&wgpu::RequestAdapterOptions::default())
@@ -45,8 +69,8 @@ pub async fn search(
         .await
         .expect("Failed to get GPU device.");
 
-    println!("[GPU] Device: {}", adapter.get_info().name);
-    println!("[GPU] Batch size: {} keys per round", BATCH_SIZE);
+    eprintln!("[GPU] Device: {}", adapter.get_info().name);
+    eprintln!("[GPU] Batch size: {} keys per round", BATCH_SIZE);
 
     // --- Shader and Pipeline Setup ---
     let shader_source = std::include_str!("shader.wgsl");
@@ -62,16 +86,19 @@ pub async fn search(
         entry_point: "main",
     });
 
+    let matcher = Matcher::new(&targets, filled);
+
     // --- Main GPU Loop ---
     let secp = Secp256k1::new();
     let mut round = 0;
+    // Allocated once, so growing it never leaves copies of the keys behind.
+    let mut cpu_keys = KeyBatch(Vec::with_capacity(BATCH_SIZE as usize));
 
     while !should_stop.load(Ordering::Relaxed) {
         round += 1;
         let round_start_time = Instant::now();
 
         // --- 1. CPU: Generate a batch of keys ---
-        let mut cpu_keys = Vec::with_capacity(BATCH_SIZE as usize);
         let mut gpu_keys_data = Vec::with_capacity(BATCH_SIZE as usize);
 
         for _ in 0..BATCH_SIZE {
@@ -80,7 +107,7 @@ pub async fn search(
             let mut key_bytes = [0u8; 64];
             key_bytes.copy_from_slice(&serialized_pubkey[1..]); // Exclude the 0x04 prefix
 
-            cpu_keys.push((privkey, pubkey));
+            cpu_keys.0.push((privkey, pubkey));
             gpu_keys_data.push(GpuPublicKey { data: key_bytes });
         }
 
@@ -122,8 +149,10 @@ pub async fn search(
         let mut encoder =
             device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
         {
-            let mut compute_pass =
-                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: None,
+            });
             compute_pass.set_pipeline(&compute_pipeline);
             compute_pass.set_bind_group(0, &bind_group, &[]);
             // Each workgroup handles 64 keys
@@ -162,26 +191,26 @@ pub async fn search(
         // We would iterate through them, do the Base58Check encoding, and check the suffix.
         // For this example, we will just check one for demonstration.
 
-        let (privkey, pubkey) = &cpu_keys[0];
-        let address = public_key_to_tron_address(pubkey); // Re-calculate on CPU for now
-        for suffix in &suffixes {
-            if address.ends_with(suffix) {
-                let found = FoundWallet {
-                    address,
-                    private_key_hex: privkey.display_secret().to_string(),
-                };
-                if sender.send(found).is_ok() {
-                    println!("[GPU] Found a match and sent it. Stopping.");
-                    should_stop.store(true, Ordering::Relaxed);
-                }
+        let (privkey, pubkey) = &cpu_keys.0[0];
+        // Re-calculate on CPU for now
+        attempts.add(1, should_stop);
+        if let Some((address, target)) = matcher.check_key(&pubkey.serialize_uncompressed()) {
+            let found = FoundWallet {
+                address,
+                secret: SecretBytes::from(privkey),
+                target,
+                mnemonic: None,
+            };
+            if sender.send(found).is_err() {
                 return;
             }
         }
         drop(data);
         staging_buffer.unmap();
+        cpu_keys.clear();
 
         let duration = round_start_time.elapsed();
         let speed = BATCH_SIZE as f64 / duration.as_secs_f64();
-        print!("\r[GPU] Round {}: {:.2e} keys/sec", round, speed);
+        eprint!("\r[GPU] Round {}: {:.2e} keys/sec", round, speed);
     }
 }
@@ -0,0 +1,371 @@
+//! src/keystore.rs
+
+use crate::evm::evm_address_bytes;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use secp256k1::rand::RngCore;
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use tiny_keccak::{Hasher, Keccak};
+use zeroize::Zeroizing;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Key derivation function protecting a keystore file.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeystoreKdf {
+    /// scrypt (n = 2^18, r = 8, p = 1), the geth and TronLink default.
+    #[default]
+    Scrypt,
+    /// PBKDF2-HMAC-SHA256 with 262144 iterations.
+    Pbkdf2,
+}
+
+/// The KDF section of a keystore file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kdf", content = "kdfparams", rename_all = "lowercase")]
+pub enum KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u32,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        dklen: usize,
+        c: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+impl KdfParams {
+    /// The usual wallet parameters for `kdf`, with a fresh random salt.
+    pub fn standard(kdf: KeystoreKdf) -> Self {
+        let mut salt = [0u8; 32];
+        secp256k1::rand::thread_rng().fill_bytes(&mut salt);
+        let salt = hex::encode(salt);
+        match kdf {
+            KeystoreKdf::Scrypt => KdfParams::Scrypt {
+                dklen: 32,
+                n: 1 << 18,
+                r: 8,
+                p: 1,
+                salt,
+            },
+            KeystoreKdf::Pbkdf2 => KdfParams::Pbkdf2 {
+                dklen: 32,
+                c: 262_144,
+                prf: "hmac-sha256".to_string(),
+                salt,
+            },
+        }
+    }
+
+    /// Stretches `password` into the 32-byte key the cipher and MAC keys come from.
+    fn derive_key(&self, password: &[u8]) -> Result<[u8; 32], KeystoreError> {
+        let mut key = [0u8; 32];
+        match self {
+            KdfParams::Scrypt {
+                dklen,
+                n,
+                r,
+                p,
+                salt,
+            } => {
+                let salt = decode_hex("salt", salt)?;
+                if *dklen != 32 || !n.is_power_of_two() {
+                    return Err(KeystoreError::Unsupported("scrypt parameters"));
+                }
+                let params = scrypt::Params::new(n.trailing_zeros() as u8, *r, *p, 32)
+                    .map_err(|_| KeystoreError::Unsupported("scrypt parameters"))?;
+                scrypt::scrypt(password, &salt, &params, &mut key)
+                    .map_err(|_| KeystoreError::Unsupported("scrypt parameters"))?;
+            }
+            KdfParams::Pbkdf2 {
+                dklen,
+                c,
+                prf,
+                salt,
+            } => {
+                let salt = decode_hex("salt", salt)?;
+                if *dklen != 32 || prf != "hmac-sha256" {
+                    return Err(KeystoreError::Unsupported("PBKDF2 parameters"));
+                }
+                pbkdf2::pbkdf2_hmac::<Sha256>(password, &salt, *c, &mut key);
+            }
+        }
+        Ok(key)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CipherParams {
+    iv: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Crypto {
+    cipher: String,
+    cipherparams: CipherParams,
+    ciphertext: String,
+    #[serde(flatten)]
+    kdf: KdfParams,
+    mac: String,
+}
+
+/// A Web3 Secret Storage (version 3) keystore file, as written by geth and
+/// imported by TronLink, MetaMask and most other wallets.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Keystore {
+    version: u32,
+    id: String,
+    /// Hex of the 20-byte account hash. Tron addresses are this hash behind `0x41`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    crypto: Crypto,
+}
+
+/// Why a keystore file could not be decrypted.
+#[derive(Debug, PartialEq)]
+pub enum KeystoreError {
+    Json(String),
+    Unsupported(&'static str),
+    InvalidHex(&'static str),
+    WrongPassword,
+    InvalidKey,
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::Json(e) => write!(f, "Not a keystore file: {}", e),
+            KeystoreError::Unsupported(what) => write!(f, "Unsupported {}", what),
+            KeystoreError::InvalidHex(field) => write!(f, "Invalid hex in `{}`", field),
+            KeystoreError::WrongPassword => {
+                write!(f, "Wrong password (the MAC does not match)")
+            }
+            KeystoreError::InvalidKey => write!(f, "The decrypted private key is invalid"),
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {}
+
+fn decode_hex(field: &'static str, value: &str) -> Result<Vec<u8>, KeystoreError> {
+    hex::decode(value).map_err(|_| KeystoreError::InvalidHex(field))
+}
+
+/// The keystore MAC: Keccak-256 of the second half of the derived key and the ciphertext.
+fn mac(derived_key: &[u8; 32], ciphertext: &[u8]) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    keccak.update(&derived_key[16..]);
+    keccak.update(ciphertext);
+    let mut mac = [0u8; 32];
+    keccak.finalize(&mut mac);
+    mac
+}
+
+/// Random (version 4) UUID for the `id` field.
+fn random_uuid() -> String {
+    let mut bytes = [0u8; 16];
+    secp256k1::rand::thread_rng().fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+impl Keystore {
+    /// Encrypts a private key with AES-128-CTR under a key derived from `password`.
+    pub fn encrypt(secret: &SecretKey, password: &[u8], kdf: KdfParams) -> Self {
+        let derived_key = Zeroizing::new(
+            kdf.derive_key(password)
+                .expect("standard KDF parameters are supported"),
+        );
+        let mut iv = [0u8; 16];
+        secp256k1::rand::thread_rng().fill_bytes(&mut iv);
+
+        let mut ciphertext = secret.secret_bytes();
+        Aes128Ctr::new(derived_key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+
+        let public_key = secret.public_key(secp256k1::SECP256K1);
+        Self {
+            version: 3,
+            id: random_uuid(),
+            address: Some(hex::encode(evm_address_bytes(
+                &public_key.serialize_uncompressed(),
+            ))),
+            crypto: Crypto {
+                cipher: "aes-128-ctr".to_string(),
+                cipherparams: CipherParams {
+                    iv: hex::encode(iv),
+                },
+                ciphertext: hex::encode(ciphertext),
+                kdf,
+                mac: hex::encode(mac(&derived_key, &ciphertext)),
+            },
+        }
+    }
+
+    /// Recovers the private key, checking the MAC first so that a wrong password is
+    /// reported as such instead of yielding a garbage key.
+    pub fn decrypt(&self, password: &[u8]) -> Result<SecretKey, KeystoreError> {
+        if self.version != 3 {
+            return Err(KeystoreError::Unsupported("keystore version"));
+        }
+        if self.crypto.cipher != "aes-128-ctr" {
+            return Err(KeystoreError::Unsupported("cipher"));
+        }
+        let derived_key = Zeroizing::new(self.crypto.kdf.derive_key(password)?);
+        let mut plaintext = Zeroizing::new(decode_hex("ciphertext", &self.crypto.ciphertext)?);
+        let iv = decode_hex("iv", &self.crypto.cipherparams.iv)?;
+        if iv.len() != 16 {
+            return Err(KeystoreError::InvalidHex("iv"));
+        }
+        if hex::encode(mac(&derived_key, &plaintext)) != self.crypto.mac.to_lowercase() {
+            return Err(KeystoreError::WrongPassword);
+        }
+
+        Aes128Ctr::new(derived_key[..16].into(), iv.as_slice().into())
+            .apply_keystream(&mut plaintext[..]);
+        SecretKey::from_slice(&plaintext).map_err(|_| KeystoreError::InvalidKey)
+    }
+
+    /// The account hash stored in the file, if any, in lowercase hex.
+    pub fn address_hex(&self) -> Option<String> {
+        self.address
+            .as_deref()
+            .map(|a| a.trim_start_matches("0x").to_lowercase())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, KeystoreError> {
+        serde_json::from_str(json).map_err(|e| KeystoreError::Json(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("keystore serializes to JSON")
+    }
+}
+
+/// Writes found keys as keystore files into a directory, named after their address.
+pub struct KeystoreWriter {
+    pub dir: PathBuf,
+    pub password: Zeroizing<String>,
+    pub kdf: KeystoreKdf,
+}
+
+impl KeystoreWriter {
+    /// Encrypts `secret` into `<dir>/<address>.json`, readable by the owner only and
+    /// never replacing an existing file.
+    pub fn write(&self, secret: &SecretKey, address: &str) -> io::Result<PathBuf> {
+        let keystore = Keystore::encrypt(
+            secret,
+            self.password.as_bytes(),
+            KdfParams::standard(self.kdf),
+        );
+        let path = self.dir.join(format!("{}.json", address));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&path)?;
+        file.write_all(keystore.to_json().as_bytes())?;
+        file.sync_all()?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    /// Cheap parameters so the tests don't spend seconds in the KDF.
+    fn light_scrypt() -> KdfParams {
+        KdfParams::Scrypt {
+            dklen: 32,
+            n: 1 << 10,
+            r: 8,
+            p: 1,
+            salt: "ab".repeat(32),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let secret = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let keystore = Keystore::encrypt(&secret, b"hunter2", light_scrypt());
+        let parsed = Keystore::from_json(&keystore.to_json()).unwrap();
+
+        assert_eq!(parsed.decrypt(b"hunter2").unwrap(), secret);
+        assert_eq!(
+            parsed.decrypt(b"hunter3").unwrap_err(),
+            KeystoreError::WrongPassword
+        );
+    }
+
+    #[test]
+    fn test_writer_keeps_files_private() {
+        let dir = std::env::temp_dir().join(format!("fwa-keystores-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let writer = KeystoreWriter {
+            dir: dir.clone(),
+            password: Zeroizing::new("hunter2".to_string()),
+            kdf: KeystoreKdf::Pbkdf2,
+        };
+        let secret = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let path = writer.write(&secret, "TAddress").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert!(writer.write(&secret, "TAddress").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pbkdf2_vector() {
+        // The PBKDF2 test vector from the Web3 Secret Storage definition.
+        let json = r#"{
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+                "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+                "kdf": "pbkdf2",
+                "kdfparams": {
+                    "c": 262144,
+                    "dklen": 32,
+                    "prf": "hmac-sha256",
+                    "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+                },
+                "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+            },
+            "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+            "version": 3
+        }"#;
+        let secret = Keystore::from_json(json)
+            .unwrap()
+            .decrypt(b"testpassword")
+            .unwrap();
+        assert_eq!(
+            secret,
+            SecretKey::from_str("7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d")
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! src/lib.rs
+//!
+//! Vanity address search for Tron, EVM and Bitcoin, usable as a library. Start with
+//! [`search::SearchBuilder`]; the modules below are the building blocks it uses
+//! (address encodings, key generation, matching, keystore and output formats).
+
+pub mod address;
+mod affix;
+pub mod bench;
+pub mod btc;
+pub mod chain;
+pub mod contains;
+pub mod estimate;
+pub mod evm;
+pub mod keystore;
+pub mod mnemonic;
+pub mod output;
+pub mod pattern;
+pub mod patterns_file;
+pub mod quota;
+mod residue;
+pub mod score;
+pub mod search;
+pub mod secret;
+pub mod split_key;
+pub mod validate;
+mod walk;
+pub mod worker;
+
+// Conditionally compile the GPU worker module only when the 'gpu' feature is enabled.
+#[cfg(feature = "gpu")]
+mod gpu_worker;
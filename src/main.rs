@@ -1,149 +1,791 @@
 //! src/main.rs
 
-mod address;
 mod cli;
-mod worker;
 
-// Conditionally compile the GPU worker module only when the 'gpu' feature is enabled.
-#[cfg(feature = "gpu")]
-mod gpu_worker;
-
-use crate::cli::Cli;
-use crate::worker::search;
-use clap::Parser;
+use crate::cli::{Cli, Command, KeyArgs, PrivateKeyArgs, SearchArgs, TargetArgs};
+use clap::{Parser, ValueEnum};
+use fancy_wallet_address::bench::{self, Matching, Stage};
+use fancy_wallet_address::btc::{from_wif, to_wif};
+use fancy_wallet_address::chain::Chain;
+use fancy_wallet_address::estimate::Estimate;
+use fancy_wallet_address::evm::evm_address_bytes;
+use fancy_wallet_address::keystore::{Keystore, KeystoreWriter};
+use fancy_wallet_address::output::{OutputFormat, ResultWriter, SyncedFile, WalletRecord};
+use fancy_wallet_address::search::{Search, SearchBuilder, SearchHandle, StopHandle, StopReason};
+use fancy_wallet_address::split_key::combine;
+use fancy_wallet_address::worker::{KeyOptions, KeyStrategy};
 use notify_rust::Notification;
-use std::io::{stdout, Write};
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
-use std::sync::{mpsc, Arc};
+use secp256k1::SecretKey;
+use std::fs;
+use std::io::{stderr, stdout, Stdout, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
+use zeroize::Zeroizing;
 
-const BASE58_CHARS: f64 = 58.0;
+/// Exit code of a search that found everything it was asked for.
+const EXIT_COMPLETED: i32 = 0;
+/// Exit code of anything that went wrong, including bad arguments.
+const EXIT_ERROR: i32 = 1;
+/// Exit code of a search that ran out of its --max-time or --max-attempts budget.
+/// (2 is what clap exits with on invalid usage.)
+const EXIT_BUDGET_EXHAUSTED: i32 = 3;
+/// Exit code of a search stopped by Ctrl-C or SIGTERM (128 + SIGINT, as shells do).
+const EXIT_INTERRUPTED: i32 = 130;
 
 fn main() {
     // Parse command-line arguments
-    let cli = Cli::parse();
+    match Cli::parse().command {
+        Command::Search(args) => run_search(&args),
+        Command::Estimate {
+            targets,
+            keys,
+            speed,
+        } => run_estimate(&targets, &keys, speed),
+        Command::Verify {
+            address,
+            key,
+            chain,
+        } => run_verify(&address, &key, chain),
+        Command::Derive { key, chain } => run_derive(&key, chain),
+        Command::Bench {
+            chain,
+            threads,
+            duration,
+            strategy,
+        } => run_bench(chain, threads, duration, strategy),
+        Command::Keygen => run_keygen(),
+        Command::Combine {
+            secret_file,
+            offset,
+            chain,
+        } => run_combine(secret_file.as_deref(), &offset, chain),
+        Command::Decrypt {
+            file,
+            password_file,
+            chain,
+            show_private_key,
+        } => run_decrypt(&file, password_file.as_deref(), chain, show_private_key),
+    }
+}
 
-    // --- GPU PATH ---
-    if cli.gpu {
-        #[cfg(feature = "gpu")]
-        {
-            // GPU feature is enabled, run the async GPU worker.
-            println!("[*] GPU mode selected.");
-            let (sender, receiver) = mpsc::channel();
-            let should_stop = Arc::new(AtomicBool::new(false));
-
-            // The GPU worker is an async function, so we use pollster to block and run it.
-            let gpu_future = gpu_worker::search(cli.suffixes, sender, &should_stop);
-            pollster::block_on(gpu_future);
-
-            if let Ok(found) = receiver.try_recv() {
-                println!("\n🎉 GPU Found a match!");
-                println!("----------------------------------------");
-                println!("Address:      {}", found.address);
-                println!("Private Key:  {}", found.private_key_hex);
-                println!("----------------------------------------");
+/// `search`: checks the options, opens the outputs and runs the search.
+fn run_search(args: &SearchArgs) -> ! {
+    // The builder rejects patterns no address can match; they would search forever.
+    let search = match args.search_builder().and_then(SearchBuilder::build) {
+        Ok(search) => search,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+    print_warnings(&search);
+    if args.gpu && !cfg!(feature = "gpu") {
+        // GPU flag was used, but the feature was not enabled at compile time.
+        eprintln!("⚠️ Warning: GPU mode selected, but the program was not compiled with the 'gpu' feature.");
+        eprintln!("Falling back to CPU mode. To enable GPU, compile with: cargo build --release --features gpu");
+    }
+
+    // Open the output file before searching, so a bad path fails now rather than
+    // when the first key turns up.
+    let output = args.output.as_ref().map(|path| {
+        let file = match SyncedFile::open(path, args.append) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                eprintln!(
+                    "❌ {} already exists. Pass --append to add results to it.",
+                    path.display()
+                );
+                std::process::exit(EXIT_ERROR);
+            }
+            Err(e) => {
+                eprintln!("❌ Cannot open {}: {}", path.display(), e);
+                std::process::exit(EXIT_ERROR);
             }
+        };
+        if !file.had_content {
+            ResultWriter::new(args.format, file)
+        } else if args.format == OutputFormat::Json {
+            eprintln!(
+                "❌ A JSON array cannot be appended to; use --format ndjson or csv with --append."
+            );
+            std::process::exit(EXIT_ERROR);
+        } else {
+            Ok(ResultWriter::appending(args.format, file))
         }
-        #[cfg(not(feature = "gpu"))]
-        {
-            // GPU flag was used, but the feature was not enabled at compile time.
-            println!("⚠️ Warning: GPU mode selected, but the program was not compiled with the 'gpu' feature.");
-            println!("Falling back to CPU mode. To enable GPU, compile with: cargo build --release --features gpu");
-            run_cpu_search(cli);
+        .unwrap_or_else(|e| {
+            eprintln!("❌ Cannot write to {}: {}", path.display(), e);
+            std::process::exit(EXIT_ERROR);
+        })
+    });
+
+    // Ask for the keystore password before searching, not when the first key turns up.
+    let keystore = args.keystore.as_ref().map(|dir| {
+        if args.split_key.is_some() {
+            eprintln!("❌ --keystore needs full private keys and cannot be used with --split-key.");
+            std::process::exit(EXIT_ERROR);
+        }
+        if let Err(e) = fs::create_dir_all(dir) {
+            eprintln!(
+                "❌ Cannot create keystore directory {}: {}",
+                dir.display(),
+                e
+            );
+            std::process::exit(EXIT_ERROR);
+        }
+        match read_password(args.password_file.as_deref(), true) {
+            Ok(password) => KeystoreWriter {
+                dir: dir.clone(),
+                password,
+                kdf: args.keystore_kdf,
+            },
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(EXIT_ERROR);
+            }
         }
+    });
+    search_and_exit(args, search, Sinks { keystore, output })
+}
+
+/// `keygen`: the first step of a split-key search, run by the key owner.
+fn run_keygen() {
+    let secret = SecretKey::new(&mut secp256k1::rand::thread_rng());
+    println!("Secret Key:   {}", secret.display_secret());
+    println!("Public Key:   {}", secret.public_key(secp256k1::SECP256K1));
+    println!("----------------------------------------");
+    println!("Keep the secret key private. Give only the public key to the searcher:");
+    println!("  fancy_wallet_address search --split-key <PUBLIC_KEY> --suffixes ...");
+}
+
+fn print_warnings(search: &Search) {
+    for warning in search.warnings() {
+        eprintln!("⚠️ Warning: {}", warning);
     }
-    // --- CPU PATH ---
-    else {
-        run_cpu_search(cli);
+}
+
+/// `combine`: turns an offset found by a split-key search into the final key.
+fn run_combine(secret_file: Option<&Path>, offset: &SecretKey, chain: Chain) {
+    let mut secret = read_split_secret(secret_file).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(EXIT_ERROR);
+    });
+    let combined = combine(&secret, offset);
+    secret.non_secure_erase();
+    let mut key = match combined {
+        Ok(key) => key,
+        Err(e) => {
+            eprintln!("❌ Could not combine the keys: {}", e);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+    let public_key = key.public_key(secp256k1::SECP256K1);
+    println!(
+        "Address:      {}",
+        chain.address_from_uncompressed(&public_key.serialize_uncompressed())
+    );
+    println!("Private Key:  {}", key.display_secret());
+    if chain.is_bitcoin() {
+        println!("WIF:          {}", to_wif(&key.secret_bytes()));
     }
+    key.non_secure_erase();
 }
 
-/// The original CPU-based search logic.
-fn run_cpu_search(cli: Cli) {
-    // --- Calculate and print expected attempts ---
-    let total_prob: f64 = cli
-        .suffixes
-        .iter()
-        .map(|s| 1.0 / BASE58_CHARS.powi(s.len() as i32))
-        .sum();
-    let expected_attempts = (1.0 / total_prob) * cli.count as f64;
+/// Reads the `keygen` secret for `combine`, in hex, from the first line of `file`
+/// or a hidden prompt.
+fn read_split_secret(file: Option<&Path>) -> Result<SecretKey, String> {
+    let input = match file {
+        Some(file) => Zeroizing::new(
+            fs::read_to_string(file)
+                .map_err(|e| format!("Cannot read secret file {}: {}", file.display(), e))?,
+        ),
+        None => Zeroizing::new(
+            rpassword::prompt_password("Secret key: ")
+                .map_err(|e| format!("Cannot read the secret key: {}", e))?,
+        ),
+    };
+    let input = input.lines().next().unwrap_or_default().trim();
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    hex::decode_to_slice(input.strip_prefix("0x").unwrap_or(input), &mut bytes[..])
+        .map_err(|_| "Not a secret key: expected 64 hex digits".to_string())?;
+    SecretKey::from_slice(&bytes[..]).map_err(|_| "The secret key is out of range".to_string())
+}
+
+/// Reads a password from the first line of `file`, or prompts for it on the terminal
+/// (twice, when `confirm` is set).
+fn read_password(file: Option<&Path>, confirm: bool) -> Result<Zeroizing<String>, String> {
+    if let Some(file) = file {
+        let contents = Zeroizing::new(
+            fs::read_to_string(file)
+                .map_err(|e| format!("Cannot read password file {}: {}", file.display(), e))?,
+        );
+        return Ok(Zeroizing::new(
+            contents.lines().next().unwrap_or_default().to_string(),
+        ));
+    }
+    let password = Zeroizing::new(
+        rpassword::prompt_password("Keystore password: ")
+            .map_err(|e| format!("Cannot read password: {}", e))?,
+    );
+    if confirm {
+        if password.is_empty() {
+            return Err("The keystore password must not be empty".to_string());
+        }
+        let again = Zeroizing::new(
+            rpassword::prompt_password("Repeat password: ")
+                .map_err(|e| format!("Cannot read password: {}", e))?,
+        );
+        if again != password {
+            return Err("Passwords do not match".to_string());
+        }
+    }
+    Ok(password)
+}
+
+/// `decrypt`: checks that a keystore file opens and shows which address it holds.
+fn run_decrypt(file: &Path, password_file: Option<&Path>, chain: Chain, show_private_key: bool) {
+    let result = fs::read_to_string(file)
+        .map_err(|e| format!("Cannot read {}: {}", file.display(), e))
+        .and_then(|json| Keystore::from_json(&json).map_err(|e| e.to_string()))
+        .and_then(|keystore| {
+            let password = read_password(password_file, false)?;
+            let key = keystore
+                .decrypt(password.as_bytes())
+                .map_err(|e| e.to_string())?;
+            Ok((keystore, key))
+        });
+    let (keystore, key) = match result {
+        Ok(decrypted) => decrypted,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+
+    let public_key = key
+        .public_key(secp256k1::SECP256K1)
+        .serialize_uncompressed();
+    let address = chain.address_from_uncompressed(&public_key);
+    // The file records the 20-byte account hash that Tron and EVM addresses share.
+    if let Some(recorded) = keystore.address_hex() {
+        if recorded != hex::encode(evm_address_bytes(&public_key)) {
+            eprintln!(
+                "❌ The key decrypts, but does not match the address recorded in the file (0x{}).",
+                recorded
+            );
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+    println!("✅ Keystore decrypted successfully.");
+    println!("Address:      {}", address);
+    if show_private_key {
+        println!("Private Key:  {}", key.display_secret());
+        if chain.is_bitcoin() {
+            println!("WIF:          {}", to_wif(&key.secret_bytes()));
+        }
+    }
+}
+
+/// The completion probabilities `estimate` and `search` quote times for.
+const CONFIDENCE_LEVELS: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// Formats a number of attempts, in scientific notation past `u64::MAX`, more keys
+/// than any search can check.
+fn format_attempts(attempts: f64) -> String {
+    if attempts < u64::MAX as f64 {
+        (attempts.round() as u64).to_string()
+    } else {
+        format!("{:.2e}", attempts)
+    }
+}
+
+/// How long `estimate` measures the speed when no --speed is given.
+const ESTIMATE_MEASUREMENT: Duration = Duration::from_secs(2);
+
+/// `estimate`: the work a search needs and how long it should take, without running it.
+fn run_estimate(targets: &TargetArgs, keys: &KeyArgs, speed: Option<f64>) {
+    let search = match targets
+        .builder()
+        .map(|builder| keys.apply(builder))
+        .and_then(SearchBuilder::build)
+    {
+        Ok(search) => search,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+    print_warnings(&search);
+    let Some(estimate) = Estimate::new(search.targets(), search.total()) else {
+        eprintln!(
+            "❌ Regex, glob and score searches cannot be estimated; give prefixes, suffixes or substrings."
+        );
+        std::process::exit(EXIT_ERROR);
+    };
+    if !search.targets().patterns.is_empty() {
+        eprintln!("[*] Regex/glob patterns are not included in the estimate.");
+    }
+    let speed = match speed {
+        Some(speed) if speed > 0.0 => speed,
+        Some(_) => {
+            eprintln!("❌ --speed must be positive");
+            std::process::exit(EXIT_ERROR);
+        }
+        None => {
+            eprintln!(
+                "[*] Measuring the speed on {} CPU threads for {}...",
+                search.threads(),
+                humantime::format_duration(ESTIMATE_MEASUREMENT)
+            );
+            bench::keys_per_second(
+                targets.chain,
+                search.keys(),
+                Matching::ResidueSuffix,
+                search.threads(),
+                ESTIMATE_MEASUREMENT,
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("❌ Cannot measure the speed: {}", e);
+                std::process::exit(EXIT_ERROR);
+            })
+        }
+    };
+    if estimate.probability() == 0.0 {
+        println!("Difficulty:   impossible, no address can match");
+        std::process::exit(EXIT_ERROR);
+    }
+    let attempts_and_time = |attempts: f64| {
+        let seconds = (attempts / speed).ceil();
+        if seconds < u64::MAX as f64 {
+            format!(
+                "{} attempts, {}",
+                format_attempts(attempts),
+                humantime::format_duration(Duration::from_secs(seconds as u64))
+            )
+        } else {
+            format!("{} attempts", format_attempts(attempts))
+        }
+    };
+    let difficulty = 1.0 / estimate.probability();
+    if difficulty < u64::MAX as f64 {
+        println!("Difficulty:   1 in {} keys", format_attempts(difficulty));
+    } else {
+        println!(
+            "Difficulty:   1 in {} keys, impossible in practice",
+            format_attempts(difficulty)
+        );
+    }
+    println!("Results:      {}", estimate.wanted());
+    println!("Speed:        {} keys/sec", speed as u64);
     println!(
-        "[*] Estimated attempts required: {} (to find {})",
-        expected_attempts as u64, cli.count
+        "Expected:     {}",
+        attempts_and_time(estimate.expected_attempts())
+    );
+    for confidence in CONFIDENCE_LEVELS {
+        println!(
+            "{:<14}{}",
+            format!("{}% chance:", confidence * 100.0),
+            attempts_and_time(estimate.attempts_for(confidence))
+        );
+    }
+}
+
+/// Reads the private key of `verify` and `derive`, in hex or WIF, from the command
+/// line or a hidden prompt.
+fn read_private_key(args: &PrivateKeyArgs) -> Result<SecretKey, String> {
+    let input = match &args.private_key {
+        Some(key) => Zeroizing::new(key.clone()),
+        None => Zeroizing::new(
+            rpassword::prompt_password("Private key: ")
+                .map_err(|e| format!("Cannot read the private key: {}", e))?,
+        ),
+    };
+    let input = input.trim();
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    let hex_key = input.strip_prefix("0x").unwrap_or(input);
+    if hex::decode_to_slice(hex_key, &mut bytes[..]).is_err() {
+        *bytes = from_wif(input)
+            .ok_or("Not a private key: expected 64 hex digits or a WIF".to_string())?;
+    }
+    SecretKey::from_slice(&bytes[..]).map_err(|_| "The private key is out of range".to_string())
+}
+
+/// `verify`: checks that a private key controls an address, e.g. before funding it.
+fn run_verify(address: &str, key: &PrivateKeyArgs, chain: Option<Chain>) {
+    let Some(chain) = chain.or_else(|| Chain::from_address(address)) else {
+        eprintln!(
+            "❌ Cannot tell which chain {} belongs to; pass --chain.",
+            address
+        );
+        std::process::exit(EXIT_ERROR);
+    };
+    let mut key = read_private_key(key).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(EXIT_ERROR);
+    });
+    let derived = chain.address_from_uncompressed(
+        &key.public_key(secp256k1::SECP256K1)
+            .serialize_uncompressed(),
     );
+    key.non_secure_erase();
+    // Hex and bech32 addresses are the same in any letter case.
+    let matches = if chain.folds_case(false) {
+        derived.eq_ignore_ascii_case(address)
+    } else {
+        derived == address
+    };
+    if !matches {
+        eprintln!(
+            "❌ The key does not control {}; its {} address is {}.",
+            address,
+            chain.name(),
+            derived
+        );
+        std::process::exit(EXIT_ERROR);
+    }
+    println!("✅ The key controls {} ({}).", derived, chain.name());
+}
 
-    // Determine the number of threads to use
-    let num_threads = cli.threads.unwrap_or_else(num_cpus::get);
-    rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .build_global()
-        .unwrap();
+/// `derive`: shows the public key and addresses a private key controls.
+fn run_derive(key: &PrivateKeyArgs, chain: Option<Chain>) {
+    let mut key = read_private_key(key).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(EXIT_ERROR);
+    });
+    let public_key = key.public_key(secp256k1::SECP256K1);
+    let uncompressed = public_key.serialize_uncompressed();
+    match chain {
+        Some(chain) => {
+            let address = chain.address_from_uncompressed(&uncompressed);
+            println!("Address:      {}", address);
+            println!(
+                "Hex Address:  {}",
+                chain
+                    .hex_address(&address)
+                    .expect("derived addresses are well-formed")
+            );
+        }
+        None => {
+            for chain in Chain::value_variants() {
+                println!(
+                    "{:<17}{}",
+                    format!("{}:", chain.name()),
+                    chain.address_from_uncompressed(&uncompressed)
+                );
+            }
+        }
+    }
+    println!("Public Key:   {}", hex::encode(uncompressed));
+    println!("Compressed:   {}", public_key);
+    if chain.is_none_or(Chain::is_bitcoin) {
+        println!("WIF:          {}", to_wif(&key.secret_bytes()));
+    }
+    key.non_secure_erase();
+}
 
+/// `bench`: the speed of each step of the hot loop on one core, then keys per second
+/// for each key strategy, then for each way of matching a target.
+fn run_bench(
+    chain: Chain,
+    threads: Option<usize>,
+    duration: Duration,
+    strategy: Option<KeyStrategy>,
+) {
+    let threads = threads.unwrap_or_else(num_cpus::get);
+    let strategies = match strategy {
+        Some(strategy) => vec![strategy],
+        None => KeyStrategy::value_variants().to_vec(),
+    };
+    eprintln!(
+        "[*] Measuring each stage and strategy for {}...",
+        humantime::format_duration(duration)
+    );
+    // The version heads the table, so saved runs can be compared across releases.
     println!(
-        "🔍 Searching for addresses ending with: {:?} on {} CPU threads...",
-        cli.suffixes, num_threads
+        "fancy_wallet_address {}, {} CPU threads",
+        env!("CARGO_PKG_VERSION"),
+        threads
     );
+    println!();
+    println!("{:<24}{:>12}", "Stage (Tron)", "ops/sec/core");
+    for stage in Stage::ALL {
+        let speed = bench::stage_ops_per_second(stage, duration);
+        println!("{:<24}{:>12}", stage.name(), speed as u64);
+    }
+    println!();
+    println!(
+        "{:<24}{:>12}",
+        format!("Search ({})", chain.name()),
+        "keys/sec"
+    );
+    let mut failed = false;
+    for strategy in strategies {
+        let keys = KeyOptions {
+            strategy,
+            mnemonic_words: 12,
+            mnemonic_indices: 1,
+        };
+        let name = strategy
+            .to_possible_value()
+            .expect("no strategy is skipped");
+        let label = format!("CPU x{} {}", threads, name.get_name());
+        let speed = bench::keys_per_second(chain, keys, Matching::ResidueSuffix, threads, duration);
+        match speed {
+            Ok(speed) => println!("{:<24}{:>12}", label, speed as u64),
+            Err(e) => {
+                eprintln!("❌ {}: {}", label, e);
+                failed = true;
+            }
+        }
+    }
+    // Matching is compared with the cheapest keys, where its share of the time is largest.
+    let keys = KeyOptions {
+        strategy: strategy.unwrap_or(KeyStrategy::Walk),
+        mnemonic_words: 12,
+        mnemonic_indices: 1,
+    };
+    let name = keys
+        .strategy
+        .to_possible_value()
+        .expect("no strategy is skipped");
+    println!();
+    println!(
+        "{:<24}{:>12}",
+        format!("Matching ({})", name.get_name()),
+        "keys/sec"
+    );
+    for matching in Matching::ALL {
+        if !matching.applies_to(chain) {
+            continue;
+        }
+        match bench::keys_per_second(chain, keys, matching, threads, duration) {
+            Ok(speed) => println!("{:<24}{:>12}", matching.name(), speed as u64),
+            Err(e) => {
+                eprintln!("❌ {}: {}", matching.name(), e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(EXIT_ERROR);
+    }
+}
+
+/// Where found wallets are saved besides stdout.
+struct Sinks {
+    keystore: Option<KeystoreWriter>,
+    output: Option<ResultWriter<SyncedFile>>,
+}
+
+/// Describes the search on stderr, runs it and exits with its outcome.
+fn search_and_exit(args: &SearchArgs, search: Search, mut sinks: Sinks) -> ! {
+    let targets = search.targets();
+    let total = search.total();
+
+    // --- Calculate and print expected attempts ---
+    if let Some(estimate) = Estimate::new(targets, total) {
+        if estimate.probability() == 0.0 && targets.patterns.is_empty() {
+            eprintln!("❌ No address can match these targets.");
+            std::process::exit(EXIT_ERROR);
+        }
+        let quantiles: Vec<String> = CONFIDENCE_LEVELS
+            .iter()
+            .map(|&confidence| {
+                format!(
+                    "{}%: {}",
+                    confidence * 100.0,
+                    format_attempts(estimate.attempts_for(confidence))
+                )
+            })
+            .collect();
+        eprintln!(
+            "[*] Expected attempts: {} to find {} ({})",
+            format_attempts(estimate.expected_attempts()),
+            estimate.wanted(),
+            quantiles.join(", ")
+        );
+    }
+    if !targets.patterns.is_empty() {
+        eprintln!("[*] Regex/glob patterns are not included in the estimate.");
+    }
+    if !targets.prefixes.is_empty() {
+        eprintln!(
+            "🔍 Searching for addresses starting with: {:?}",
+            targets.prefixes
+        );
+    }
+    if !targets.narrowing_prefixes.is_empty() {
+        eprintln!(
+            "🔍 Searching for addresses starting with: {:?} and ending with: {:?}",
+            targets.narrowing_prefixes,
+            &targets.suffixes[..targets.narrowed_suffixes]
+        );
+    }
+    if targets.suffixes.len() > targets.narrowed_suffixes {
+        eprintln!(
+            "🔍 Searching for addresses ending with: {:?}",
+            &targets.suffixes[targets.narrowed_suffixes..]
+        );
+    }
+    if !targets.contains.is_empty() {
+        let contains: Vec<String> = targets.contains.iter().map(|c| c.to_string()).collect();
+        eprintln!("🔍 Searching for addresses containing: {:?}", contains);
+    }
+    if !targets.patterns.is_empty() {
+        eprintln!(
+            "🔍 Searching for addresses matching: {:?}",
+            targets.patterns
+        );
+    }
+    if let Some(top) = search.top() {
+        eprintln!(
+            "🔍 Scoring every address and keeping the best {}; stop with Ctrl-C or a budget.",
+            top
+        );
+    }
+    if let Some(split_key) = &targets.split_key {
+        eprintln!(
+            "[*] Split-key search from {}; results are offsets, not private keys.",
+            split_key
+        );
+    }
+    if targets.eip55 {
+        eprintln!("[*] Matching the EIP-55 checksum casing exactly.");
+    } else if targets.ignore_case {
+        eprintln!("[*] Matching is case-insensitive.");
+    }
+    if let Some(max) = args.max_time {
+        eprintln!(
+            "[*] Stopping after {} at most.",
+            humantime::format_duration(max)
+        );
+    }
+    if let Some(max) = args.max_attempts {
+        eprintln!("[*] Stopping after {} attempts at most.", max);
+    }
+    if args.gpu && cfg!(feature = "gpu") {
+        eprintln!("[*] GPU mode selected.");
+    } else {
+        eprintln!(
+            "[*] Running on {} CPU threads ({:?} key strategy)...",
+            search.threads(),
+            search.keys().strategy
+        );
+    }
 
-    // Setup for communication between threads
-    let (sender, receiver) = mpsc::channel();
-    let found_count = AtomicUsize::new(0);
-    let should_stop = Arc::new(AtomicBool::new(false));
-    let attempts = Arc::new(AtomicU64::new(0));
+    let mut handle = search.start();
+    handle_signals(handle.stop_handle());
 
     // --- Speed monitor thread (if requested) ---
-    if cli.show_speed {
-        let should_stop_clone = Arc::clone(&should_stop);
-        let attempts_clone = Arc::clone(&attempts);
+    if args.show_speed {
+        let stop = handle.stop_handle();
+        let progress = handle.progress();
         thread::spawn(move || {
             let mut last_check_attempts = 0;
             let check_interval = Duration::from_secs(2);
-            while !should_stop_clone.load(Ordering::Relaxed) {
+            while !stop.is_stopped() {
                 thread::sleep(check_interval);
-                let current_attempts = attempts_clone.load(Ordering::Relaxed);
+                let current_attempts = progress.attempts();
                 let speed =
                     (current_attempts - last_check_attempts) as f64 / check_interval.as_secs_f64();
                 last_check_attempts = current_attempts;
-                print!("\r[*] CPU Speed: {} checks/sec", speed as u64);
-                let _ = stdout().flush();
+                eprint!("\r[*] CPU Speed: {} checks/sec", speed as u64);
+                let _ = stderr().flush();
             }
         });
     }
 
-    // Start the search in the background
-    let suffixes = cli.suffixes.clone();
-    let should_stop_clone = Arc::clone(&should_stop);
-    let attempts_clone = Arc::clone(&attempts);
-    thread::spawn(move || {
-        search(
-            suffixes,
-            sender,
-            &should_stop_clone,
-            &attempts_clone,
-            num_threads,
-        );
+    let write_failed = collect_results(&mut handle, args, &mut sinks);
+    exit_with_summary(&handle, write_failed)
+}
+
+/// Stops the search on Ctrl-C or SIGTERM. The workers are only asked to stop, so
+/// results already found are still written; a second signal exits at once.
+fn handle_signals(stop: StopHandle) {
+    let signalled = AtomicBool::new(false);
+    let handler = ctrlc::set_handler(move || {
+        if signalled.swap(true, Ordering::Relaxed) {
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+        eprintln!("\n[*] Interrupted; stopping the workers (press Ctrl-C again to quit now)...");
+        stop.stop();
     });
+    if let Err(e) = handler {
+        eprintln!("⚠️ Warning: cannot handle Ctrl-C gracefully: {}", e);
+    }
+}
+
+/// Main thread loop: writes out results as the search hands them over, until the
+/// workers stop, either because every quota is filled or because the search was
+/// interrupted or ran out of budget. Returns whether writing a result failed.
+fn collect_results(handle: &mut SearchHandle, args: &SearchArgs, sinks: &mut Sinks) -> bool {
+    let mut writer = match ResultWriter::new(args.format, stdout()) {
+        Ok(writer) => writer,
+        Err(e) => {
+            eprintln!("❌ Cannot write to stdout: {}", e);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+    let mut write_failed = false;
 
     // Main thread waits for results
-    for found in receiver {
-        let current_count = found_count.fetch_add(1, Ordering::SeqCst);
+    while let Some(result) = handle.next() {
+        let found = &result.wallet;
+        let stats = handle.stats();
 
         // Clear the speed line before printing result
-        if cli.show_speed {
-            print!("\r{}", " ".repeat(40));
-            println!();
+        if args.show_speed {
+            eprint!("\r{}", " ".repeat(40));
+            eprintln!();
         }
 
-        // Print the found wallet details
-        println!("\n🎉 Found a match! ({}/{})", current_count + 1, cli.count);
-        println!("----------------------------------------");
-        println!("Address:      {}", found.address);
-        println!("Private Key:  {}", found.private_key_hex);
-        println!("----------------------------------------");
+        let saved = sinks.keystore.as_ref().and_then(|writer| {
+            let mut key = found.secret.to_secret_key().expect("key was verified");
+            let saved = writer.write(&key, &found.address);
+            key.non_secure_erase();
+            // Print the key rather than lose it.
+            saved
+                .map_err(|e| eprintln!("❌ Failed to write the keystore file: {}", e))
+                .ok()
+        });
+        let record = WalletRecord::new(
+            &result,
+            args.targets.chain,
+            args.split_key.is_some(),
+            saved.as_deref(),
+        );
+        // The file first: it is the copy that survives a closed terminal.
+        if let Some(output) = &mut sinks.output {
+            if let Err(e) = output.write(&record, stats.found, stats.total) {
+                eprintln!("❌ Failed to write the result to the output file: {}", e);
+                write_failed = true;
+            }
+        }
+        if let Err(e) = writer.write(&record, stats.found, stats.total) {
+            eprintln!("❌ Failed to write the result: {}", e);
+            write_failed = true;
+        }
+
+        // In score mode, only a new best is worth a notification.
+        if let Some(board) = handle.leaderboard() {
+            if board.entries()[0].address == found.address {
+                eprintln!(
+                    "🥇 New best so far: {} (score {})",
+                    found.address,
+                    board.entries()[0].score
+                );
+            } else {
+                continue;
+            }
+        }
 
         // Send a desktop notification
         if let Err(e) = Notification::new()
-            .summary("Tron Vanity Address Found!")
+            .summary(&format!(
+                "{} Vanity Address Found!",
+                args.targets.chain.name()
+            ))
             .body(&format!("Address: {}", found.address))
             .timeout(Duration::from_secs(10))
             .show()
@@ -152,17 +794,66 @@ fn run_cpu_search(cli: Cli) {
         }
 
         // Check if we have found enough addresses
-        if current_count + 1 >= cli.count {
-            println!("\n✅ Desired count reached. Exiting.");
-            should_stop.store(true, Ordering::Relaxed);
-            // Give a moment for other threads to see the flag
-            thread::sleep(Duration::from_millis(100));
-            std::process::exit(0);
-        } else {
-            println!(
-                "\n🔍 Continuing search for the next address on {} threads...",
-                num_threads
-            );
+        if stats.found >= stats.total {
+            eprintln!("\n✅ Desired count reached. Stopping the workers...");
+        } else if !handle.stop_handle().is_stopped() && handle.leaderboard().is_none() {
+            eprintln!("\n🔍 Continuing search for the next address...");
+        }
+    }
+    finish_output(&mut writer, sinks);
+    write_failed
+}
+
+/// Closes the result outputs (the JSON array brackets) before exiting.
+fn finish_output(writer: &mut ResultWriter<Stdout>, sinks: &mut Sinks) {
+    if let Some(output) = &mut sinks.output {
+        if let Err(e) = output.finish() {
+            eprintln!("❌ Failed to finish the output file: {}", e);
         }
     }
-}
\ No newline at end of file
+    let _ = writer.finish();
+}
+
+/// Prints the final statistics to stderr and exits with the code for how the
+/// search ended.
+fn exit_with_summary(handle: &SearchHandle, write_failed: bool) -> ! {
+    let stats = handle.stats();
+    if stats.discarded > 0 {
+        eprintln!(
+            "❌ Internal error: {} found keys did not derive their address and were discarded.",
+            stats.discarded
+        );
+    }
+    let scoring = handle.leaderboard().is_some();
+    let (status, code) = match handle.stop_reason() {
+        _ if write_failed => ("failed", EXIT_ERROR),
+        // A score search has nothing to complete: a budget or Ctrl-C is how it is
+        // meant to end, with the leaderboard as its result.
+        Some(StopReason::AttemptLimit | StopReason::TimeLimit | StopReason::Stopped) if scoring => {
+            ("completed", EXIT_COMPLETED)
+        }
+        Some(StopReason::Completed) => ("completed", EXIT_COMPLETED),
+        // Only the signal handler stops the search.
+        Some(StopReason::Stopped) => ("interrupted", EXIT_INTERRUPTED),
+        Some(StopReason::AttemptLimit) => ("stopped at --max-attempts", EXIT_BUDGET_EXHAUSTED),
+        Some(StopReason::TimeLimit) => ("stopped at --max-time", EXIT_BUDGET_EXHAUSTED),
+        Some(StopReason::Aborted) | None => ("stopped unexpectedly", EXIT_ERROR),
+    };
+    eprintln!("\n[*] Search {}.", status);
+    eprintln!("    Attempts:   {}", stats.attempts);
+    eprintln!(
+        "    Elapsed:    {}",
+        humantime::format_duration(Duration::from_millis(stats.elapsed.as_millis() as u64))
+    );
+    eprintln!("    Avg. speed: {} checks/sec", stats.speed() as u64);
+    match handle.leaderboard() {
+        Some(board) => {
+            eprintln!("    Leaderboard:");
+            for (rank, entry) in board.entries().iter().enumerate() {
+                eprintln!("    {:>3}. {:>5}  {}", rank + 1, entry.score, entry.address);
+            }
+        }
+        None => eprintln!("    Found:      {}/{}", stats.found, stats.total),
+    }
+    std::process::exit(code);
+}
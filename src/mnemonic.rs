@@ -0,0 +1,189 @@
+//! src/mnemonic.rs
+
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use secp256k1::rand::RngCore;
+use secp256k1::{PublicKey, Scalar, SecretKey, SECP256K1};
+use sha2::Sha512;
+use std::fmt;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Marks a BIP32 index as hardened (`'`).
+const HARDENED: u32 = 1 << 31;
+
+/// Where a key found by a mnemonic search comes from.
+#[derive(Clone, PartialEq)]
+pub struct MnemonicOrigin {
+    pub phrase: Zeroizing<String>,
+    pub purpose: u32,
+    pub coin_type: u32,
+    pub index: u32,
+}
+
+impl fmt::Debug for MnemonicOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MnemonicOrigin(<redacted>, {})", self.path())
+    }
+}
+
+impl MnemonicOrigin {
+    pub fn path(&self) -> String {
+        format!(
+            "m/{}'/{}'/0'/0/{}",
+            self.purpose, self.coin_type, self.index
+        )
+    }
+
+    /// Derives the private key again from the phrase, from scratch.
+    pub fn secret_key(&self) -> Option<SecretKey> {
+        let mnemonic = Mnemonic::parse(self.phrase.as_str()).ok()?;
+        let (key, _) =
+            MnemonicKeys::from_mnemonic(&mnemonic, self.purpose, self.coin_type).key(self.index)?;
+        Some(key)
+    }
+}
+
+/// A BIP32 extended private key: the key plus the chain code its children need.
+struct ExtendedKey {
+    key: SecretKey,
+    chain_code: [u8; 32],
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in data {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+impl Drop for ExtendedKey {
+    fn drop(&mut self) {
+        self.key.non_secure_erase();
+        self.chain_code.zeroize();
+    }
+}
+
+impl ExtendedKey {
+    fn master(seed: &[u8]) -> Self {
+        let i = Zeroizing::new(hmac_sha512(b"Bitcoin seed", &[seed]));
+        Self {
+            key: SecretKey::from_slice(&i[..32]).expect("master key is valid"),
+            chain_code: i[32..].try_into().unwrap(),
+        }
+    }
+
+    /// CKDpriv. Fails for the astronomically unlikely indices BIP32 says to skip.
+    fn child(&self, index: u32) -> Option<Self> {
+        let i = Zeroizing::new(if index & HARDENED != 0 {
+            let key = Zeroizing::new(self.key.secret_bytes());
+            hmac_sha512(&self.chain_code, &[&[0], &key[..], &index.to_be_bytes()])
+        } else {
+            let public_key = self.key.public_key(SECP256K1).serialize();
+            hmac_sha512(&self.chain_code, &[&public_key, &index.to_be_bytes()])
+        });
+        let tweak = Scalar::from_be_bytes(i[..32].try_into().unwrap()).ok()?;
+        Some(Self {
+            key: self.key.add_tweak(&tweak).ok()?,
+            chain_code: i[32..].try_into().unwrap(),
+        })
+    }
+}
+
+/// The keys of one mnemonic along a BIP44-style account path
+/// `m/purpose'/coin'/0'/0/i`, for consecutive address indices `i`.
+pub struct MnemonicKeys {
+    pub phrase: Zeroizing<String>,
+    purpose: u32,
+    coin_type: u32,
+    /// The `m/purpose'/coin'/0'/0` node all addresses derive from.
+    external: ExtendedKey,
+}
+
+impl MnemonicKeys {
+    /// Generates a fresh random mnemonic of `words` words (12, 15, 18, 21 or 24).
+    pub fn random(rng: &mut impl RngCore, words: usize, purpose: u32, coin_type: u32) -> Self {
+        let mut entropy = Zeroizing::new([0u8; 32]);
+        let entropy = &mut entropy[..words / 3 * 4];
+        rng.fill_bytes(entropy);
+        let mnemonic =
+            Mnemonic::from_entropy(entropy).expect("entropy length matches the word count");
+        Self::from_mnemonic(&mnemonic, purpose, coin_type)
+    }
+
+    /// Derives the account node of an existing mnemonic (empty passphrase).
+    pub fn from_mnemonic(mnemonic: &Mnemonic, purpose: u32, coin_type: u32) -> Self {
+        let seed = Zeroizing::new(mnemonic.to_seed_normalized(""));
+        let external = [purpose | HARDENED, coin_type | HARDENED, HARDENED, 0]
+            .into_iter()
+            .try_fold(ExtendedKey::master(&seed[..]), |node, index| {
+                node.child(index)
+            })
+            .expect("account path derives");
+        Self {
+            phrase: Zeroizing::new(mnemonic.to_string()),
+            purpose,
+            coin_type,
+            external,
+        }
+    }
+
+    /// The private and public key at address index `index`.
+    pub fn key(&self, index: u32) -> Option<(SecretKey, PublicKey)> {
+        let child = self.external.child(index)?;
+        Some((child.key, child.key.public_key(SECP256K1)))
+    }
+
+    pub fn origin(&self, index: u32) -> MnemonicOrigin {
+        MnemonicOrigin {
+            phrase: self.phrase.clone(),
+            purpose: self.purpose,
+            coin_type: self.coin_type,
+            index,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+
+    #[test]
+    fn test_known_mnemonic_addresses() {
+        let mnemonic = Mnemonic::parse(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let address = |chain: Chain| {
+            let (purpose, coin_type) = chain.bip44_path();
+            let (_, public_key) = MnemonicKeys::from_mnemonic(&mnemonic, purpose, coin_type)
+                .key(0)
+                .unwrap();
+            chain.address_from_uncompressed(&public_key.serialize_uncompressed())
+        };
+
+        assert_eq!(
+            address(Chain::Evm),
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94"
+        );
+        assert_eq!(address(Chain::Tron), "TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdH");
+        // BIP84 test vector.
+        assert_eq!(
+            address(Chain::BtcSegwit),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+    }
+
+    #[test]
+    fn test_random_word_counts() {
+        let mut rng = secp256k1::rand::thread_rng();
+        for words in [12, 15, 18, 21, 24] {
+            let keys = MnemonicKeys::random(&mut rng, words, 44, 195);
+            assert_eq!(keys.phrase.split(' ').count(), words);
+            let origin = keys.origin(3);
+            assert_eq!(origin.path(), "m/44'/195'/0'/0/3");
+            assert_eq!(origin.secret_key(), keys.key(3).map(|(key, _)| key));
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! src/output.rs
+
+use crate::btc::to_wif;
+use crate::chain::Chain;
+use crate::search::SearchResult;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+use zeroize::Zeroizing;
+
+/// How found wallets are written to stdout. Progress and notices always go to
+/// stderr, so stdout only ever carries results.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable blocks.
+    #[default]
+    Text,
+    /// A single JSON array, closed when the search ends.
+    Json,
+    /// One JSON object per line, written as each wallet is found.
+    Ndjson,
+    /// Comma-separated values with a header row.
+    Csv,
+}
+
+/// A found wallet, rendered for output. Fields that do not apply (the private key
+/// of a split-key result, the seed phrase of a random key, ...) are `null` in
+/// JSON and empty in CSV, so every record has the same shape.
+///
+/// This is where found secrets are turned into text; every rendering is zeroized.
+#[derive(Serialize)]
+pub struct WalletRecord {
+    pub address: String,
+    /// See [`Chain::hex_address`]; `41...` for Tron.
+    pub hex_address: String,
+    /// The public key in hex; compressed for Bitcoin, uncompressed otherwise.
+    pub public_key: String,
+    pub private_key: Option<Zeroizing<String>>,
+    /// Bitcoin only: the private key in Wallet Import Format.
+    pub wif: Option<Zeroizing<String>>,
+    /// Split-key searches: the offset to `combine` with the secret key.
+    pub offset: Option<Zeroizing<String>>,
+    pub mnemonic: Option<Zeroizing<String>>,
+    pub path: Option<String>,
+    /// The keystore file the key was saved to instead of being printed.
+    pub keystore: Option<String>,
+    /// The target the address matched.
+    pub pattern: String,
+    /// The matched target's label, e.g. the customer or order it was searched for.
+    pub label: Option<String>,
+    /// Score mode: what the address scored.
+    pub score: Option<u32>,
+    /// Keys checked across all threads when the wallet was reported.
+    pub attempts: u64,
+    /// When the wallet was reported, in RFC 3339 (UTC).
+    pub timestamp: String,
+}
+
+impl WalletRecord {
+    /// Renders a verified result. A result that was saved to a `keystore` file does
+    /// not carry its private key.
+    pub fn new(
+        result: &SearchResult,
+        chain: Chain,
+        split_key: bool,
+        keystore: Option<&Path>,
+    ) -> Self {
+        let (found, public_key) = (&result.wallet, &result.public_key);
+        let secret_hex = || Zeroizing::new(hex::encode(found.secret.expose()));
+        let shows_key = !split_key && keystore.is_none();
+        Self {
+            address: found.address.clone(),
+            hex_address: chain
+                .hex_address(&found.address)
+                .expect("found addresses are well-formed"),
+            public_key: if chain.is_bitcoin() {
+                hex::encode(public_key.serialize())
+            } else {
+                hex::encode(public_key.serialize_uncompressed())
+            },
+            private_key: shows_key.then(secret_hex),
+            wif: (shows_key && chain.is_bitcoin())
+                .then(|| Zeroizing::new(to_wif(found.secret.expose()))),
+            offset: split_key.then(secret_hex),
+            mnemonic: found.mnemonic.as_ref().map(|origin| origin.phrase.clone()),
+            path: found.mnemonic.as_ref().map(|origin| origin.path()),
+            keystore: keystore.map(|path| path.display().to_string()),
+            pattern: result.pattern.clone(),
+            label: result.label.clone(),
+            score: result.score,
+            attempts: result.attempts,
+            timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        }
+    }
+
+    fn write_text(&self, out: &mut impl Write, found: usize, total: usize) -> io::Result<()> {
+        if let Some(score) = self.score {
+            writeln!(out, "\n🏆 Scored {} (candidate {})", score, found)?;
+        } else {
+            writeln!(
+                out,
+                "\n🎉 Found a match for \"{}\"! ({}/{})",
+                self.pattern, found, total
+            )?;
+        }
+        writeln!(out, "----------------------------------------")?;
+        if let Some(label) = &self.label {
+            writeln!(out, "Label:        {}", label)?;
+        }
+        writeln!(out, "Address:      {}", self.address)?;
+        if let Some(path) = &self.keystore {
+            writeln!(out, "Keystore:     {}", path)?;
+        }
+        if let Some(offset) = &self.offset {
+            writeln!(out, "Offset:       {}", **offset)?;
+            writeln!(out, "(combine it with your secret key: fancy_wallet_address combine --secret <SECRET> --offset <OFFSET>)")?;
+        }
+        if let (Some(mnemonic), Some(path)) = (&self.mnemonic, &self.path) {
+            writeln!(out, "Mnemonic:     {}", **mnemonic)?;
+            writeln!(out, "Path:         {}", path)?;
+        }
+        if let Some(private_key) = &self.private_key {
+            writeln!(out, "Private Key:  {}", **private_key)?;
+        }
+        if let Some(wif) = &self.wif {
+            writeln!(out, "WIF:          {}", **wif)?;
+        }
+        writeln!(out, "----------------------------------------")
+    }
+}
+
+/// Writes found wallets in the chosen format, flushing after each one so nothing
+/// is lost if the process is killed.
+pub struct ResultWriter<W: Write> {
+    format: OutputFormat,
+    out: W,
+    records: usize,
+}
+
+impl<W: Write> ResultWriter<W> {
+    /// Starts the output: the CSV header row, or the opening bracket of a JSON array.
+    pub fn new(format: OutputFormat, mut out: W) -> io::Result<Self> {
+        match format {
+            OutputFormat::Csv => {
+                let mut csv = csv::Writer::from_writer(&mut out);
+                csv.write_record(CSV_HEADER)?;
+                csv.flush()?;
+            }
+            OutputFormat::Json => out.write_all(b"[")?,
+            OutputFormat::Text | OutputFormat::Ndjson => {}
+        }
+        out.flush()?;
+        Ok(Self {
+            format,
+            out,
+            records: 0,
+        })
+    }
+
+    /// Continues an existing output that already has its header, e.g. a CSV file
+    /// opened with `--append`. A JSON array cannot be continued this way.
+    pub fn appending(format: OutputFormat, out: W) -> Self {
+        Self {
+            format,
+            out,
+            records: 0,
+        }
+    }
+
+    /// Writes one wallet; `found` of `total` wanted are shown in the text format.
+    pub fn write(&mut self, record: &WalletRecord, found: usize, total: usize) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Text => record.write_text(&mut self.out, found, total)?,
+            OutputFormat::Json => {
+                let separator = if self.records == 0 { "\n" } else { ",\n" };
+                self.out.write_all(separator.as_bytes())?;
+                let json = Zeroizing::new(serde_json::to_string(record)?);
+                self.out.write_all(json.as_bytes())?;
+            }
+            OutputFormat::Ndjson => {
+                let json = Zeroizing::new(serde_json::to_string(record)?);
+                writeln!(self.out, "{}", *json)?;
+            }
+            OutputFormat::Csv => {
+                let mut csv = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(&mut self.out);
+                csv.serialize(record)?;
+                csv.flush()?;
+            }
+        }
+        self.records += 1;
+        self.out.flush()
+    }
+
+    /// Ends the output, closing the JSON array.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            let close: &[u8] = if self.records == 0 { b"]\n" } else { b"\n]\n" };
+            self.out.write_all(close)?;
+        }
+        self.out.flush()
+    }
+}
+
+/// The `--output` file. Every flush is an `fsync`, so a result is on disk before
+/// the search moves on, whatever happens to the terminal or the machine later.
+pub struct SyncedFile {
+    file: File,
+    /// Whether the file already had content when it was opened.
+    pub had_content: bool,
+}
+
+impl SyncedFile {
+    /// Creates `path` readable by the owner only. An existing file is refused unless
+    /// `append` is set, in which case results are added to its end, provided no one
+    /// else can read it.
+    pub fn open(path: &Path, append: bool) -> io::Result<Self> {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = match options.open(path) {
+            Ok(file) => {
+                sync_parent_dir(path)?;
+                file
+            }
+            Err(e) if append && e.kind() == io::ErrorKind::AlreadyExists => {
+                let file = OpenOptions::new().append(true).open(path)?;
+                check_private(&file)?;
+                file
+            }
+            Err(e) => return Err(e),
+        };
+        let had_content = file.metadata()?.len() > 0;
+        Ok(Self { file, had_content })
+    }
+}
+
+/// Syncs the directory holding a newly created file, so its entry survives a crash
+/// along with its contents.
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Refuses to add keys to a file that other users can read.
+fn check_private(file: &File) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = file.metadata()?.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "the file is accessible to other users (mode {:o}); run `chmod 600` on it first",
+                    mode
+                ),
+            ));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = file;
+    Ok(())
+}
+
+impl Write for SyncedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+}
+
+/// CSV columns, in the order of the [`WalletRecord`] fields.
+const CSV_HEADER: [&str; 14] = [
+    "address",
+    "hex_address",
+    "public_key",
+    "private_key",
+    "wif",
+    "offset",
+    "mnemonic",
+    "path",
+    "keystore",
+    "pattern",
+    "label",
+    "score",
+    "attempts",
+    "timestamp",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::SecretBytes;
+    use crate::worker::FoundWallet;
+    use secp256k1::SecretKey;
+
+    fn record(chain: Chain) -> WalletRecord {
+        let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let public_key = key.public_key(secp256k1::SECP256K1);
+        let result = SearchResult {
+            wallet: FoundWallet {
+                address: chain.address_from_uncompressed(&public_key.serialize_uncompressed()),
+                secret: SecretBytes::from(&key),
+                target: 0,
+                mnemonic: None,
+            },
+            public_key,
+            pattern: "a,b".to_string(),
+            label: None,
+            score: None,
+            attempts: 42,
+        };
+        WalletRecord::new(&result, chain, false, None)
+    }
+
+    #[test]
+    fn test_machine_readable_formats() {
+        let tron = record(Chain::Tron);
+        assert!(tron.hex_address.starts_with("41"));
+        assert_eq!(tron.hex_address.len(), 42);
+
+        let mut writer = ResultWriter::new(OutputFormat::Json, Vec::new()).unwrap();
+        writer.write(&tron, 1, 2).unwrap();
+        writer.write(&record(Chain::Btc), 2, 2).unwrap();
+        writer.finish().unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&writer.out).unwrap();
+        assert_eq!(json[0]["address"], tron.address.as_str());
+        assert_eq!(json[0]["private_key"], "11".repeat(32));
+        assert_eq!(json[0]["wif"], serde_json::Value::Null);
+        assert_eq!(json[1]["hex_address"].as_str().unwrap().len(), 42);
+        assert!(json[1]["wif"].is_string());
+        assert_eq!(json[1]["attempts"], 42);
+
+        let mut writer = ResultWriter::new(OutputFormat::Csv, Vec::new()).unwrap();
+        writer.write(&tron, 1, 1).unwrap();
+        let csv = String::from_utf8(writer.out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        // Empty optional fields, and the pattern quoted for its comma.
+        assert!(lines[1].contains(&format!("{},,,,,,\"a,b\",,,42,", "11".repeat(32))));
+
+        // The header names the record's fields.
+        let mut csv = csv::Writer::from_writer(Vec::new());
+        csv.serialize(&tron).unwrap();
+        let serialized = String::from_utf8(csv.into_inner().unwrap()).unwrap();
+        assert_eq!(serialized.lines().next(), Some(lines[0]));
+    }
+
+    #[test]
+    fn test_output_file_is_private_and_never_replaced() {
+        let path = std::env::temp_dir().join(format!("fwa-output-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let file = SyncedFile::open(&path, false).unwrap();
+        assert!(!file.had_content);
+        let mut writer = ResultWriter::new(OutputFormat::Csv, file).unwrap();
+        writer.write(&record(Chain::Tron), 1, 1).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let refused = SyncedFile::open(&path, false).err().unwrap();
+        assert_eq!(refused.kind(), io::ErrorKind::AlreadyExists);
+
+        let file = SyncedFile::open(&path, true).unwrap();
+        assert!(file.had_content);
+        let mut writer = ResultWriter::appending(OutputFormat::Csv, file);
+        writer.write(&record(Chain::Tron), 1, 1).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 3);
+
+        // Keys are never added to a file others can read.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let readable = std::fs::Permissions::from_mode(0o644);
+            std::fs::set_permissions(&path, readable).unwrap();
+            let refused = SyncedFile::open(&path, true).err().unwrap();
+            assert_eq!(refused.kind(), io::ErrorKind::PermissionDenied);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+//! src/pattern.rs
+
+use crate::chain::Chain;
+use regex::{Regex, RegexBuilder};
+use regex_syntax::hir::literal::{ExtractKind, Extractor};
+use regex_syntax::hir::Look;
+use regex_syntax::ParserBuilder;
+
+/// Suffix pre-filters with more alternatives than this are not worth checking.
+const MAX_PREFILTER_LITERALS: usize = 64;
+
+/// A regular expression or glob matched against the whole address string.
+///
+/// Patterns are written either as regexes (`^T.{5}KiWi`) or as globs (`T*88?88`),
+/// where `*` matches any run of characters and `?` matches exactly one. Prefix the
+/// pattern with `re:` or `glob:` to force one interpretation; otherwise anything
+/// containing regex syntax beyond `*` and `?` is treated as a regex.
+pub struct AddressPattern {
+    regex: Regex,
+    /// Literal endings one of which every matching address must have, if known.
+    required_suffixes: Option<Vec<Vec<u8>>>,
+    ignore_case: bool,
+}
+
+impl AddressPattern {
+    /// Compiles a pattern from its command-line form.
+    pub fn compile(pattern: &str, ignore_case: bool) -> Result<Self, regex::Error> {
+        let expression = to_regex(pattern);
+        let regex = RegexBuilder::new(&expression)
+            .case_insensitive(ignore_case)
+            .build()?;
+        Ok(Self {
+            regex,
+            required_suffixes: required_suffixes(&expression),
+            ignore_case,
+        })
+    }
+
+    /// Checks the address, rejecting most candidates with a cheap suffix comparison
+    /// before running the regex engine.
+    #[inline]
+    pub fn is_match(&self, address: &str) -> bool {
+        if let Some(suffixes) = &self.required_suffixes {
+            let bytes = address.as_bytes();
+            let has_suffix = |s: &Vec<u8>| {
+                bytes.len() >= s.len()
+                    && (if self.ignore_case {
+                        bytes[bytes.len() - s.len()..].eq_ignore_ascii_case(s)
+                    } else {
+                        bytes.ends_with(s)
+                    })
+            };
+            if !suffixes.iter().any(has_suffix) {
+                return false;
+            }
+        }
+        self.regex.is_match(address)
+    }
+}
+
+/// Rejects a pattern no address of `chain` can match: one that needs more or fewer
+/// characters than an address has, or that has to start with something other than
+/// the chain's lead. Syntax errors are left to [`AddressPattern::compile`].
+pub fn check_reachable(pattern: &str, chain: Chain, ignore_case: bool) -> Result<(), String> {
+    let expression = to_regex(pattern);
+    // Addresses are ASCII, so lengths are counted in bytes with `.` matching one.
+    let Ok(hir) = ParserBuilder::new()
+        .case_insensitive(ignore_case)
+        .unicode(false)
+        .utf8(false)
+        .build()
+        .parse(&expression)
+    else {
+        return Ok(());
+    };
+    let never = |why: String| {
+        Err(format!(
+            "Pattern \"{}\" never matches: {} (use --contains for text anywhere in the address)",
+            pattern, why
+        ))
+    };
+
+    let properties = hir.properties();
+    let anchored = properties.look_set_prefix().contains(Look::Start);
+    let whole = anchored && properties.look_set_suffix().contains(Look::End);
+    let (shortest, longest) = (chain.shortest_address_len(), chain.address_len());
+    if let Some(min) = properties.minimum_len().filter(|&min| min > longest) {
+        return never(format!(
+            "it needs {} characters, but {} addresses have at most {}",
+            min,
+            chain.name(),
+            longest
+        ));
+    }
+    if let Some(max) = properties
+        .maximum_len()
+        .filter(|&max| whole && max < shortest)
+    {
+        return never(format!(
+            "it spans at most {} characters, but {} addresses have at least {}",
+            max,
+            chain.name(),
+            shortest
+        ));
+    }
+
+    // Every match starts with one of these; each must agree with the lead.
+    if anchored {
+        let seq = Extractor::new().kind(ExtractKind::Prefix).extract(&hir);
+        let lead = chain.lead().as_bytes();
+        if let Some(literals) = seq.literals() {
+            if !literals.is_empty()
+                && !literals.iter().any(|literal| {
+                    let literal = literal.as_bytes();
+                    literal.starts_with(lead) || lead.starts_with(literal)
+                })
+            {
+                return never(format!(
+                    "{} addresses start with \"{}\"",
+                    chain.name(),
+                    chain.lead()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Translates a command-line pattern into regex syntax.
+fn to_regex(pattern: &str) -> String {
+    if let Some(expression) = pattern.strip_prefix("re:") {
+        return expression.to_string();
+    }
+    if let Some(glob) = pattern.strip_prefix("glob:") {
+        return glob_to_regex(glob);
+    }
+
+    let looks_like_regex = pattern.starts_with('^')
+        || pattern.chars().any(|c| {
+            matches!(
+                c,
+                '.' | '[' | ']' | '(' | ')' | '{' | '}' | '\\' | '|' | '+' | '$'
+            )
+        });
+    if looks_like_regex {
+        pattern.to_string()
+    } else {
+        glob_to_regex(pattern)
+    }
+}
+
+/// Rewrites a pattern to match with its own letter case handling, whatever the
+/// search-wide setting.
+pub fn with_case(pattern: &str, ignore_case: bool) -> String {
+    let flag = if ignore_case { "i" } else { "-i" };
+    format!("re:(?{}:{})", flag, to_regex(pattern))
+}
+
+/// Converts a glob into an anchored regex covering the whole address.
+fn glob_to_regex(glob: &str) -> String {
+    let mut expression = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            _ => expression.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    expression.push('$');
+    expression
+}
+
+/// Extracts the set of literal endings every match must have, when the expression
+/// is anchored to the end of the address.
+fn required_suffixes(expression: &str) -> Option<Vec<Vec<u8>>> {
+    let hir = regex_syntax::parse(expression).ok()?;
+    if !hir.properties().look_set_suffix().contains(Look::End) {
+        return None;
+    }
+
+    let seq = Extractor::new().kind(ExtractKind::Suffix).extract(&hir);
+    let literals = seq.literals()?;
+    if literals.is_empty()
+        || literals.len() > MAX_PREFILTER_LITERALS
+        || literals.iter().any(|l| l.as_bytes().is_empty())
+    {
+        return None;
+    }
+    Some(literals.iter().map(|l| l.as_bytes().to_vec()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_matching() {
+        let pattern = AddressPattern::compile("T*88?88", false).unwrap();
+        assert!(pattern.is_match("TJRyWwFs9wTFGZg3JbrVriV5incfS88x88"));
+        assert!(!pattern.is_match("TJRyWwFs9wTFGZg3JbrVriV5incfS88x8x"));
+        assert_eq!(pattern.required_suffixes, Some(vec![b"88".to_vec()]));
+    }
+
+    #[test]
+    fn test_regex_matching() {
+        let pattern = AddressPattern::compile("^T.{5}COOL$", false).unwrap();
+        assert!(pattern.is_match("TabcdeCOOL"));
+        assert!(!pattern.is_match("TJRyWwFs9wTFGZg3JbrVriV5incfSCOOL"));
+
+        // Unanchored expressions cannot be pre-filtered on the address ending.
+        let pattern = AddressPattern::compile("re:COOL", false).unwrap();
+        assert!(pattern.required_suffixes.is_none());
+        assert!(pattern.is_match("TJRyWwFs9wTFCOOLbrVriV5incfS2Qd2s"));
+    }
+
+    #[test]
+    fn test_unreachable_patterns() {
+        // A bare word is a glob for the whole address.
+        assert!(check_reachable("COOL", Chain::Tron, false).is_err());
+        assert!(check_reachable("C*", Chain::Tron, false).is_err());
+        assert!(check_reachable("re:^T.{40}$", Chain::Tron, false).is_err());
+        assert!(check_reachable("re:^x", Chain::Evm, false).is_err());
+        assert!(check_reachable("t*", Chain::Tron, true).is_ok());
+        assert!(check_reachable("T*88?88", Chain::Tron, false).is_ok());
+        assert!(check_reachable("re:COOL", Chain::Tron, false).is_ok());
+        assert!(check_reachable("re:^0x.{2}dead", Chain::Evm, false).is_ok());
+        // Legacy Bitcoin addresses can be shorter than 34 characters.
+        assert!(check_reachable("1*", Chain::Btc, false).is_ok());
+        let glob = |wildcards: usize| format!("glob:1{}", "?".repeat(wildcards));
+        assert!(check_reachable(&glob(25), Chain::Btc, false).is_ok());
+        assert!(check_reachable(&glob(24), Chain::Btc, false).is_err());
+        assert!(check_reachable(&glob(34), Chain::Btc, false).is_err());
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let pattern = AddressPattern::compile("T*cool", true).unwrap();
+        assert!(pattern.is_match("TJRyWwFs9wTFGZg3JbrVriV5incfS2CoOL"));
+        assert!(!AddressPattern::compile("T*cool", false)
+            .unwrap()
+            .is_match("TJRyWwFs9wTFGZg3JbrVriV5incfS2CoOL"));
+
+        // A pattern's own case handling wins over the search-wide one.
+        let pattern = AddressPattern::compile(&with_case("T*cool", false), true).unwrap();
+        assert!(!pattern.is_match("TJRyWwFs9wTFGZg3JbrVriV5incfS2CoOL"));
+        assert!(pattern.is_match("TJRyWwFs9wTFGZg3JbrVriV5incfS2cool"));
+        let pattern = AddressPattern::compile(&with_case("T*cool", true), false).unwrap();
+        assert!(pattern.is_match("TJRyWwFs9wTFGZg3JbrVriV5incfS2CoOL"));
+    }
+}
@@ -0,0 +1,280 @@
+//! src/patterns_file.rs
+
+use crate::contains::split_positions;
+use crate::quota::split_quota;
+use crate::search::{Target, TargetKind};
+use serde::Deserialize;
+use std::path::Path;
+
+/// How a patterns file is written, going by its extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    /// One target per line: `[prefix:|suffix:|contains:]TARGET[:QUOTA] [LABEL]`.
+    /// Lines starting with `re:` or `glob:` are patterns, others suffixes.
+    /// Substrings take a position range as on the command line (`cool@3..10`).
+    /// A leading `~` matches the line in any letter case, a leading `=` in exactly
+    /// the case written, whatever the search's own setting.
+    Text,
+    /// `[[pattern]]` tables with a `pattern` and optionally its `type` (`prefix`,
+    /// `suffix`, `contains`, `regex` or `glob`), `ignore_case`, `quota` and `label`.
+    /// Substrings can be limited to starting positions `start` up to `end`.
+    Toml,
+    /// An array of entries, or an object with a `patterns` array.
+    Json,
+}
+
+impl FileFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("toml") => FileFormat::Toml,
+            Some(e) if e.eq_ignore_ascii_case("json") => FileFormat::Json,
+            _ => FileFormat::Text,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum EntryKind {
+    Prefix,
+    #[default]
+    Suffix,
+    Contains,
+    #[serde(alias = "pattern")]
+    Regex,
+    Glob,
+}
+
+/// One target of a TOML or JSON patterns file. Only `pattern` is required.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    pattern: String,
+    #[serde(default, rename = "type")]
+    kind: EntryKind,
+    ignore_case: Option<bool>,
+    quota: Option<usize>,
+    label: Option<String>,
+    start: Option<usize>,
+    end: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct EntryList {
+    #[serde(alias = "pattern")]
+    patterns: Vec<Entry>,
+}
+
+impl TryFrom<Entry> for Target {
+    type Error = String;
+
+    fn try_from(entry: Entry) -> Result<Self, String> {
+        if entry.quota == Some(0) {
+            return Err(format!("Invalid quota for \"{}\"", entry.pattern));
+        }
+        let positions = match (entry.start, entry.end) {
+            (None, None) => None,
+            (start, end) => Some(start.unwrap_or(0)..end.unwrap_or(usize::MAX)),
+        };
+        if positions.as_ref().is_some_and(|p| p.is_empty()) {
+            return Err(format!("Invalid position range for \"{}\"", entry.pattern));
+        }
+        let (kind, value) = match entry.kind {
+            EntryKind::Prefix => (TargetKind::Prefix, entry.pattern),
+            EntryKind::Suffix => (TargetKind::Suffix, entry.pattern),
+            EntryKind::Contains => (TargetKind::Contains, entry.pattern),
+            EntryKind::Regex | EntryKind::Glob
+                if entry.pattern.starts_with("re:") || entry.pattern.starts_with("glob:") =>
+            {
+                (TargetKind::Pattern, entry.pattern)
+            }
+            EntryKind::Regex => (TargetKind::Pattern, format!("re:{}", entry.pattern)),
+            EntryKind::Glob => (TargetKind::Pattern, format!("glob:{}", entry.pattern)),
+        };
+        Ok(Target {
+            kind,
+            value,
+            quota: entry.quota,
+            ignore_case: entry.ignore_case,
+            label: entry.label,
+            positions,
+        })
+    }
+}
+
+/// Reads the targets of a patterns file, in the format its extension names.
+pub fn load(path: &Path) -> Result<Vec<Target>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    parse(&contents, FileFormat::from_path(path)).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn parse(contents: &str, format: FileFormat) -> Result<Vec<Target>, String> {
+    let entries = match format {
+        FileFormat::Text => return parse_text(contents),
+        FileFormat::Toml => {
+            toml::from_str::<EntryList>(contents)
+                .map_err(|e| e.to_string())?
+                .patterns
+        }
+        FileFormat::Json if contents.trim_start().starts_with('[') => {
+            serde_json::from_str(contents).map_err(|e| e.to_string())?
+        }
+        FileFormat::Json => {
+            serde_json::from_str::<EntryList>(contents)
+                .map_err(|e| e.to_string())?
+                .patterns
+        }
+    };
+    entries.into_iter().map(Target::try_from).collect()
+}
+
+/// Parses the line format, skipping blank lines and `#` comments.
+fn parse_text(contents: &str) -> Result<Vec<Target>, String> {
+    let mut targets = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (entry, label) = match line.split_once(char::is_whitespace) {
+            Some((entry, label)) => (entry, Some(label.trim().to_string())),
+            None => (line, None),
+        };
+        let (ignore_case, entry) = if let Some(rest) = entry.strip_prefix('~') {
+            (Some(true), rest)
+        } else if let Some(rest) = entry.strip_prefix('=') {
+            (Some(false), rest)
+        } else {
+            (None, entry)
+        };
+        let (kind, entry) = if let Some(rest) = entry.strip_prefix("prefix:") {
+            (TargetKind::Prefix, rest)
+        } else if let Some(rest) = entry.strip_prefix("suffix:") {
+            (TargetKind::Suffix, rest)
+        } else if let Some(rest) = entry.strip_prefix("contains:") {
+            (TargetKind::Contains, rest)
+        } else if entry.starts_with("re:") || entry.starts_with("glob:") {
+            (TargetKind::Pattern, entry)
+        } else {
+            (TargetKind::Suffix, entry)
+        };
+        let at_line = |e| format!("line {}: {}", number + 1, e);
+        let (value, quota) = split_quota(entry).map_err(at_line)?;
+        let (value, positions) = match kind {
+            TargetKind::Contains => split_positions(&value).map_err(at_line)?,
+            _ => (value, None),
+        };
+        targets.push(Target {
+            quota,
+            label,
+            positions,
+            ignore_case,
+            ..Target::new(kind, value)
+        });
+    }
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_lines() {
+        let targets = parse(
+            "# order book\n\n8888:3  ACME Corp, order 17\nprefix:RX\ncontains:cool@3..10:2 bob\nre:^T.{5}COOL$\n",
+            FileFormat::Text,
+        )
+        .unwrap();
+        assert_eq!(
+            targets[0],
+            Target {
+                quota: Some(3),
+                label: Some("ACME Corp, order 17".to_string()),
+                ..Target::new(TargetKind::Suffix, "8888")
+            }
+        );
+        assert_eq!(targets[1], Target::new(TargetKind::Prefix, "RX"));
+        assert_eq!(
+            targets[2],
+            Target {
+                quota: Some(2),
+                label: Some("bob".to_string()),
+                positions: Some(3..10),
+                ..Target::new(TargetKind::Contains, "cool")
+            }
+        );
+        assert_eq!(
+            targets[3],
+            Target::new(TargetKind::Pattern, "re:^T.{5}COOL$")
+        );
+
+        let targets = parse("~prefix:rx order-1\n=AbC:2\n", FileFormat::Text).unwrap();
+        assert_eq!(
+            targets,
+            [
+                Target {
+                    ignore_case: Some(true),
+                    label: Some("order-1".to_string()),
+                    ..Target::new(TargetKind::Prefix, "rx")
+                },
+                Target {
+                    ignore_case: Some(false),
+                    quota: Some(2),
+                    ..Target::new(TargetKind::Suffix, "AbC")
+                },
+            ]
+        );
+
+        let error = parse("8888\n8888:0\n", FileFormat::Text).unwrap_err();
+        assert!(error.starts_with("line 2:"));
+    }
+
+    #[test]
+    fn test_toml_and_json_entries() {
+        let toml = r#"
+            [[pattern]]
+            pattern = "8888"
+            quota = 2
+            label = "order-1"
+
+            [[pattern]]
+            pattern = "^T.{5}COOL$"
+            type = "regex"
+            ignore_case = true
+        "#;
+        let json = r#"[
+            {"pattern": "8888", "quota": 2, "label": "order-1"},
+            {"pattern": "^T.{5}COOL$", "type": "regex", "ignore_case": true}
+        ]"#;
+        let expected = vec![
+            Target {
+                quota: Some(2),
+                label: Some("order-1".to_string()),
+                ..Target::new(TargetKind::Suffix, "8888")
+            },
+            Target {
+                ignore_case: Some(true),
+                ..Target::new(TargetKind::Pattern, "re:^T.{5}COOL$")
+            },
+        ];
+        assert_eq!(parse(toml, FileFormat::Toml).unwrap(), expected);
+        assert_eq!(parse(json, FileFormat::Json).unwrap(), expected);
+        let wrapped = format!("{{\"patterns\": {}}}", json);
+        assert_eq!(parse(&wrapped, FileFormat::Json).unwrap(), expected);
+
+        let json = r#"[{"pattern": "cool", "type": "contains", "start": 2}]"#;
+        assert_eq!(
+            parse(json, FileFormat::Json).unwrap()[0].positions,
+            Some(2..usize::MAX)
+        );
+
+        assert!(parse(r#"[{"pattern": "a", "typo": 1}]"#, FileFormat::Json).is_err());
+        assert!(parse(r#"[{"pattern": "a", "type": "middle"}]"#, FileFormat::Json).is_err());
+        assert_eq!(
+            FileFormat::from_path(Path::new("orders.TOML")),
+            FileFormat::Toml
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! src/quota.rs
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Splits an optional per-pattern quota off a command-line entry (`8888:3`).
+///
+/// Only a trailing `:<digits>` counts as a quota, so regexes containing `:` (like
+/// `(?:...)` or the `re:` prefix) are left alone.
+pub fn split_quota(entry: &str) -> Result<(String, Option<usize>), String> {
+    if let Some((pattern, quota)) = entry.rsplit_once(':') {
+        if !pattern.is_empty() && !quota.is_empty() && quota.bytes().all(|b| b.is_ascii_digit()) {
+            return match quota.parse::<usize>() {
+                Ok(0) | Err(_) => Err(format!("Invalid quota in \"{}\"", entry)),
+                Ok(quota) => Ok((pattern.to_string(), Some(quota))),
+            };
+        }
+    }
+    Ok((entry.to_string(), None))
+}
+
+/// Tracks how many more results each target and the whole search still need.
+///
+/// Without any explicit quotas the search simply stops after `count` results. Once a
+/// quota is given, every target gets one (defaulting to 1) and the search runs
+/// until all of them are filled. Workers read the shared `filled` flags to stop
+/// reporting targets that are already done.
+pub struct QuotaTracker {
+    /// Results still wanted per target; `None` means no per-target limit.
+    remaining: Vec<Option<usize>>,
+    total: usize,
+    found: usize,
+    filled: Arc<[AtomicBool]>,
+}
+
+impl QuotaTracker {
+    pub fn new(quotas: &[Option<usize>], count: usize) -> Self {
+        let remaining: Vec<Option<usize>> = if quotas.iter().any(Option::is_some) {
+            quotas.iter().map(|q| Some(q.unwrap_or(1))).collect()
+        } else {
+            vec![None; quotas.len()]
+        };
+        let total = if remaining.iter().all(Option::is_some) && !remaining.is_empty() {
+            remaining.iter().flatten().sum()
+        } else {
+            count
+        };
+        Self {
+            filled: remaining.iter().map(|_| AtomicBool::new(false)).collect(),
+            remaining,
+            total,
+            found: 0,
+        }
+    }
+
+    /// Flags shared with the workers, one per target.
+    pub fn filled(&self) -> Arc<[AtomicBool]> {
+        Arc::clone(&self.filled)
+    }
+
+    /// Records a result for `target`. Returns `false` for results that arrive after
+    /// the target's quota was filled (another thread got there first).
+    pub fn accept(&mut self, target: usize) -> bool {
+        if self.is_complete() {
+            return false;
+        }
+        if let Some(remaining) = &mut self.remaining[target] {
+            if *remaining == 0 {
+                return false;
+            }
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.filled[target].store(true, Ordering::Relaxed);
+            }
+        }
+        self.found += 1;
+        true
+    }
+
+    /// Number of results accepted so far.
+    pub fn found(&self) -> usize {
+        self.found
+    }
+
+    /// Number of results the whole search is after.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.found >= self.total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_quota() {
+        assert_eq!(split_quota("8888:3"), Ok(("8888".to_string(), Some(3))));
+        assert_eq!(split_quota("COOL"), Ok(("COOL".to_string(), None)));
+        assert_eq!(
+            split_quota("re:(?:ab)$"),
+            Ok(("re:(?:ab)$".to_string(), None))
+        );
+        assert!(split_quota("8888:0").is_err());
+    }
+
+    #[test]
+    fn test_quotas_fill_independently() {
+        let mut tracker = QuotaTracker::new(&[Some(2), None], 10);
+        assert_eq!(tracker.total(), 3);
+        assert!(tracker.accept(0));
+        assert!(tracker.accept(0));
+        assert!(!tracker.accept(0));
+        assert!(tracker.filled()[0].load(Ordering::Relaxed));
+        assert!(!tracker.is_complete());
+        assert!(tracker.accept(1));
+        assert!(tracker.is_complete());
+    }
+
+    #[test]
+    fn test_plain_count() {
+        let mut tracker = QuotaTracker::new(&[None, None], 2);
+        assert!(tracker.accept(1));
+        assert!(tracker.accept(1));
+        assert!(tracker.is_complete());
+        assert!(!tracker.filled()[1].load(Ordering::Relaxed));
+    }
+}
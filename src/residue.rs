@@ -0,0 +1,129 @@
+//! src/residue.rs
+
+use crate::address::BASE58_ALPHABET;
+use rustc_hash::FxHashSet;
+
+/// Longest suffix whose modulus (58^n) still fits comfortably in a `u64`.
+/// 58^10 is just under 2^59, so products of two residues fit in a `u128`.
+pub const MAX_RESIDUE_SUFFIX_LEN: usize = 10;
+
+struct ResidueGroup {
+    /// 58^n for this suffix length.
+    modulus: u64,
+    residues: FxHashSet<u64>,
+}
+
+/// Matches Base58 suffixes arithmetically on the raw 25-byte address payload.
+///
+/// The last `n` characters of a Base58 string are the digits of the encoded value
+/// modulo 58^n, so a suffix can be turned into a residue once and compared against
+/// `payload mod 58^n` without building the address string at all. All lengths share
+/// one reduction modulo the largest 58^n, since every smaller modulus divides it.
+pub struct ResidueMatcher {
+    modulus: u64,
+    /// 2^128 mod `modulus`, for folding the high part of the payload.
+    high_factor: u64,
+    groups: Vec<ResidueGroup>,
+}
+
+impl ResidueMatcher {
+    /// Builds a matcher for exact (case-sensitive) suffixes, or `None` when a suffix is
+    /// too long or not Base58 and the string comparison has to be used instead.
+    pub fn new(suffixes: &[String]) -> Option<Self> {
+        if suffixes.is_empty() {
+            return None;
+        }
+
+        let mut groups = Vec::<ResidueGroup>::new();
+        for suffix in suffixes {
+            if suffix.len() > MAX_RESIDUE_SUFFIX_LEN {
+                return None;
+            }
+            let residue = suffix_residue(suffix)?;
+            let modulus = 58u64.pow(suffix.len() as u32);
+            match groups.iter_mut().find(|g| g.modulus == modulus) {
+                Some(group) => {
+                    group.residues.insert(residue);
+                }
+                None => groups.push(ResidueGroup {
+                    modulus,
+                    residues: FxHashSet::from_iter([residue]),
+                }),
+            }
+        }
+        groups.sort_by_key(|g| g.modulus);
+
+        let modulus = groups.last()?.modulus;
+        let two_64 = (1u128 << 64) % modulus as u128;
+        let high_factor = (two_64 * two_64 % modulus as u128) as u64;
+        Some(Self {
+            modulus,
+            high_factor,
+            groups,
+        })
+    }
+
+    /// Checks whether the Base58 encoding of `payload` ends with any of the suffixes.
+    #[inline]
+    pub fn is_match(&self, payload: &[u8; 25]) -> bool {
+        let residue = self.reduce(payload);
+        self.groups
+            .iter()
+            .any(|group| group.residues.contains(&(residue % group.modulus)))
+    }
+
+    /// Computes `payload mod modulus`, treating the payload as a big-endian integer.
+    #[inline]
+    fn reduce(&self, payload: &[u8; 25]) -> u64 {
+        let mut high = [0u8; 16];
+        high[7..].copy_from_slice(&payload[..9]);
+        let high = u128::from_be_bytes(high);
+        let low = u128::from_be_bytes(payload[9..].try_into().unwrap());
+
+        let m = self.modulus as u128;
+        // value = high * 2^128 + low; both factors are reduced below 2^59 first.
+        ((high % m * self.high_factor as u128 % m + low % m) % m) as u64
+    }
+}
+
+/// Interprets a suffix as a number in Base58.
+fn suffix_residue(suffix: &str) -> Option<u64> {
+    suffix.bytes().try_fold(0u64, |acc, c| {
+        let digit = BASE58_ALPHABET.iter().position(|&a| a == c)?;
+        Some(acc * 58 + digit as u64)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::tron_payload_from_uncompressed;
+    use secp256k1::Secp256k1;
+
+    #[test]
+    fn test_residue_agrees_with_encoding() {
+        let secp = Secp256k1::new();
+        let mut rng = secp256k1::rand::thread_rng();
+
+        for _ in 0..200 {
+            let (_, public_key) = secp.generate_keypair(&mut rng);
+            let payload = tron_payload_from_uncompressed(&public_key.serialize_uncompressed());
+            let address = bs58::encode(payload).into_string();
+
+            // Every tail of the real address must match, and a tweaked one must not.
+            let tail = address[address.len() - MAX_RESIDUE_SUFFIX_LEN..].to_string();
+            let short = address[address.len() - 3..].to_string();
+            let wrong = if short.starts_with('z') { "y" } else { "z" }.to_string() + &short[1..];
+            assert!(ResidueMatcher::new(&[tail]).unwrap().is_match(&payload));
+            assert!(ResidueMatcher::new(&[short]).unwrap().is_match(&payload));
+            assert!(!ResidueMatcher::new(&[wrong]).unwrap().is_match(&payload));
+        }
+    }
+
+    #[test]
+    fn test_unsupported_suffixes() {
+        assert!(ResidueMatcher::new(&[]).is_none());
+        assert!(ResidueMatcher::new(&["C0OL".to_string()]).is_none());
+        assert!(ResidueMatcher::new(&["A".repeat(MAX_RESIDUE_SUFFIX_LEN + 1)]).is_none());
+    }
+}
@@ -0,0 +1,202 @@
+//! src/score.rs
+
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Rates how pretty an address is; higher is better. Scorers see the address
+/// without the chain's lead (`T`, `0x`, ...), since every address shares it.
+pub trait Scorer: Send + Sync {
+    fn score(&self, body: &str) -> u32;
+}
+
+impl<F: Fn(&str) -> u32 + Send + Sync> Scorer for F {
+    fn score(&self, body: &str) -> u32 {
+        self(body)
+    }
+}
+
+impl fmt::Debug for dyn Scorer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Scorer")
+    }
+}
+
+/// The built-in scorer, see [`beauty_score`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BeautyScore;
+
+impl Scorer for BeautyScore {
+    fn score(&self, body: &str) -> u32 {
+        beauty_score(body)
+    }
+}
+
+/// Scores runs (`88888`), repeated pairs (`abab`), ascending or descending
+/// sequences (`12345`, `edcba`) and a mirrored tail (`xyzyx`).
+///
+/// Every feature counts the characters it predicts from the ones before it, and
+/// adds the square of that count: a run of five predicts four characters and
+/// scores 16. Squaring makes one long feature worth more than several short ones,
+/// which is what people see when they look at an address.
+pub fn beauty_score(body: &str) -> u32 {
+    let b = body.as_bytes();
+    let runs = streaks(b.len(), 1, |i| b[i] == b[i - 1]);
+    let ascending = streaks(b.len(), 1, |i| b[i] == b[i - 1].wrapping_add(1));
+    let descending = streaks(b.len(), 1, |i| b[i].wrapping_add(1) == b[i - 1]);
+    let pairs = streaks(b.len(), 2, |i| b[i] == b[i - 2] && b[i] != b[i - 1]);
+    runs + ascending + descending + pairs + mirrored_tail(b)
+}
+
+/// Sums the squared lengths of the streaks of consecutive indices from `start`
+/// on for which `predicted` holds.
+fn streaks(len: usize, start: usize, predicted: impl Fn(usize) -> bool) -> u32 {
+    let (mut total, mut streak) = (0, 0);
+    for i in start..len {
+        if predicted(i) {
+            streak += 1;
+        } else {
+            total += streak * streak;
+            streak = 0;
+        }
+    }
+    total + streak * streak
+}
+
+/// Scores the longest palindrome (of at least three characters) ending the
+/// address by the characters its second half mirrors. Tails of one repeated
+/// character are runs, and already scored as such.
+fn mirrored_tail(b: &[u8]) -> u32 {
+    (3..=b.len())
+        .rev()
+        .map(|len| &b[b.len() - len..])
+        .find(|tail| tail.iter().eq(tail.iter().rev()))
+        .filter(|tail| tail.iter().any(|&c| c != tail[0]))
+        .map_or(0, |tail| (tail.len() as u32 / 2).pow(2))
+}
+
+/// An address on the [`Leaderboard`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ranked {
+    pub score: u32,
+    pub address: String,
+}
+
+/// The best-scoring addresses so far, highest first. Of equal scores, the one
+/// found first ranks higher.
+#[derive(Clone, Debug)]
+pub struct Leaderboard {
+    size: usize,
+    entries: Vec<Ranked>,
+}
+
+impl Leaderboard {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            entries: Vec::with_capacity(size + 1),
+        }
+    }
+
+    /// Adds a candidate, returning its rank (0 for the best) if it makes the board.
+    pub fn insert(&mut self, score: u32, address: &str) -> Option<usize> {
+        let rank = self.entries.partition_point(|entry| entry.score >= score);
+        if rank >= self.size {
+            return None;
+        }
+        self.entries.insert(
+            rank,
+            Ranked {
+                score,
+                address: address.to_string(),
+            },
+        );
+        self.entries.truncate(self.size);
+        Some(rank)
+    }
+
+    /// The lowest score that still makes the board.
+    pub fn threshold(&self) -> u32 {
+        match self.entries.last() {
+            Some(last) if self.entries.len() == self.size => last.score.saturating_add(1),
+            _ => 0,
+        }
+    }
+
+    pub fn entries(&self) -> &[Ranked] {
+        &self.entries
+    }
+}
+
+/// Score mode as the workers see it: the scorer, and the lowest score worth
+/// reporting, which the search handle raises as its leaderboard fills.
+#[derive(Clone, Debug)]
+pub struct ScoreTarget {
+    pub scorer: Arc<dyn Scorer>,
+    pub(crate) threshold: Arc<AtomicU32>,
+    /// Plain hex addresses are scored in lowercase, the way they are matched.
+    pub(crate) folds_case: bool,
+}
+
+impl ScoreTarget {
+    pub(crate) fn new(scorer: Arc<dyn Scorer>, folds_case: bool) -> Self {
+        Self {
+            scorer,
+            threshold: Arc::new(AtomicU32::new(0)),
+            folds_case,
+        }
+    }
+
+    /// Whether an address body, already in the case it is matched in, is worth
+    /// reporting.
+    #[inline]
+    pub(crate) fn is_candidate(&self, body: &str) -> bool {
+        self.scorer.score(body) >= self.threshold.load(Ordering::Relaxed)
+    }
+
+    /// Scores an address body as displayed.
+    pub(crate) fn score(&self, body: &str) -> u32 {
+        if self.folds_case {
+            self.scorer.score(&body.to_ascii_lowercase())
+        } else {
+            self.scorer.score(body)
+        }
+    }
+
+    pub(crate) fn raise_threshold(&self, board: &Leaderboard) {
+        self.threshold.store(board.threshold(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beauty_features() {
+        assert_eq!(beauty_score("QwErTy"), 0);
+        // A run of five predicts four characters.
+        assert_eq!(beauty_score("Qw88888"), 16);
+        assert_eq!(beauty_score("Qw12345"), 16);
+        assert_eq!(beauty_score("Qwedcba"), 16);
+        assert_eq!(beauty_score("Qw7k7k7kR"), 16);
+        // "k7QQ7k": the run of two and the mirrored tail of six.
+        assert_eq!(beauty_score("Qwk7QQ7k"), 1 + 9);
+        // One long feature beats several short ones.
+        assert!(beauty_score("Qw8888") > beauty_score("Qw11Wa22Wb33"));
+    }
+
+    #[test]
+    fn test_leaderboard() {
+        let mut board = Leaderboard::new(2);
+        assert_eq!(board.threshold(), 0);
+        assert_eq!(board.insert(5, "a"), Some(0));
+        assert_eq!(board.insert(7, "b"), Some(0));
+        assert_eq!(board.threshold(), 6);
+        assert_eq!(board.insert(5, "c"), None);
+        assert_eq!(board.insert(7, "d"), Some(1));
+        let addresses: Vec<&str> = board.entries().iter().map(|e| e.address.as_str()).collect();
+        assert_eq!(addresses, ["b", "d"]);
+        assert_eq!(board.threshold(), 8);
+    }
+}
@@ -0,0 +1,1152 @@
+//! src/search.rs
+
+use crate::chain::Chain;
+use crate::contains::Contains;
+use crate::estimate::contains_probability;
+use crate::pattern::{check_reachable, with_case, AddressPattern};
+use crate::quota::QuotaTracker;
+use crate::score::{Leaderboard, ScoreTarget, Scorer};
+use crate::split_key::offset_public_key;
+use crate::validate::{
+    validate_affix_lengths, validate_prefix, validate_substring, validate_suffix,
+};
+use crate::worker::{
+    self, Attempts, CaseOverrides, FoundWallet, KeyOptions, KeyStrategy, SearchTargets,
+};
+use secp256k1::PublicKey;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// What a [`Target`] is matched as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetKind {
+    /// Right after the chain's lead, see [`SearchBuilder::prefix`].
+    Prefix,
+    Suffix,
+    /// Anywhere after the chain's lead, or within [`Target::positions`].
+    Contains,
+    /// A regex or glob, see [`AddressPattern`].
+    Pattern,
+}
+
+/// A target with all of its options, e.g. one entry of a patterns file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Target {
+    pub kind: TargetKind,
+    pub value: String,
+    /// Number of results wanted for this target.
+    pub quota: Option<usize>,
+    /// Overrides [`SearchBuilder::ignore_case`] for this target.
+    pub ignore_case: Option<bool>,
+    /// Passed on to every result for this target, e.g. the customer or order it is for.
+    pub label: Option<String>,
+    /// Substrings only: where it may start, see [`Contains::positions`].
+    pub positions: Option<Range<usize>>,
+}
+
+impl Target {
+    pub fn new(kind: TargetKind, value: impl Into<String>) -> Self {
+        Self {
+            kind,
+            value: value.into(),
+            quota: None,
+            ignore_case: None,
+            label: None,
+            positions: None,
+        }
+    }
+}
+
+/// A target on its way into [`SearchTargets`]: what results are reported under,
+/// and what is matched.
+struct Entry {
+    name: String,
+    value: String,
+    quota: Option<usize>,
+    label: Option<String>,
+    positions: Option<Range<usize>>,
+    /// Whether it is matched regardless of letter case.
+    ignore_case: bool,
+}
+
+/// Configures a vanity search. Targets can be given with a quota, the number of
+/// addresses wanted for that target; see [`crate::quota::QuotaTracker`].
+///
+/// ```
+/// use fancy_wallet_address::chain::Chain;
+/// use fancy_wallet_address::search::{SearchBuilder, StopReason};
+///
+/// let mut handle = SearchBuilder::new(Chain::Tron)
+///     .suffix("a")
+///     .threads(2)
+///     .build()
+///     .unwrap()
+///     .start();
+/// let result = handle.next().unwrap();
+/// assert!(result.wallet.address.ends_with('a'));
+/// assert!(handle.next().is_none());
+/// assert_eq!(handle.stop_reason(), Some(StopReason::Completed));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SearchBuilder {
+    chain: Chain,
+    /// Prefixes and suffixes from [`Self::prefix`] and [`Self::suffix`], which
+    /// combine.
+    combined: Vec<Target>,
+    targets: Vec<Target>,
+    ignore_case: bool,
+    eip55: bool,
+    count: usize,
+    threads: Option<usize>,
+    keys: KeyOptions,
+    split_key: Option<PublicKey>,
+    max_time: Option<Duration>,
+    max_attempts: Option<u64>,
+    gpu: bool,
+    scorer: Option<Arc<dyn Scorer>>,
+    top: usize,
+}
+
+impl SearchBuilder {
+    pub fn new(chain: Chain) -> Self {
+        Self {
+            chain,
+            combined: Vec::new(),
+            targets: Vec::new(),
+            ignore_case: false,
+            eip55: false,
+            count: 1,
+            threads: None,
+            keys: KeyOptions {
+                strategy: KeyStrategy::Random,
+                mnemonic_words: 12,
+                mnemonic_indices: 1,
+            },
+            split_key: None,
+            max_time: None,
+            max_attempts: None,
+            gpu: false,
+            scorer: None,
+            top: 10,
+        }
+    }
+
+    /// Matched right after the chain's lead (`T`, `0x`, ...). Combined with
+    /// suffixes given through [`Self::suffix`], an address must match both.
+    pub fn prefix(self, prefix: impl Into<String>) -> Self {
+        self.prefix_with_quota(prefix, None)
+    }
+
+    /// A prefix with a quota. Only allowed when there are no suffixes, since results
+    /// count towards the suffix otherwise.
+    pub fn prefix_with_quota(mut self, prefix: impl Into<String>, quota: Option<usize>) -> Self {
+        self.combined.push(Target {
+            quota,
+            ..Target::new(TargetKind::Prefix, prefix)
+        });
+        self
+    }
+
+    pub fn suffix(self, suffix: impl Into<String>) -> Self {
+        self.suffix_with_quota(suffix, None)
+    }
+
+    pub fn suffix_with_quota(mut self, suffix: impl Into<String>, quota: Option<usize>) -> Self {
+        self.combined.push(Target {
+            quota,
+            ..Target::new(TargetKind::Suffix, suffix)
+        });
+        self
+    }
+
+    /// A substring matched anywhere after the chain's lead.
+    pub fn contains(self, text: impl Into<String>) -> Self {
+        self.target(Target::new(TargetKind::Contains, text))
+    }
+
+    /// A regex or glob matched against the whole address, see [`AddressPattern`].
+    pub fn pattern(self, pattern: impl Into<String>) -> Self {
+        self.pattern_with_quota(pattern, None)
+    }
+
+    pub fn pattern_with_quota(self, pattern: impl Into<String>, quota: Option<usize>) -> Self {
+        self.target(Target {
+            quota,
+            ..Target::new(TargetKind::Pattern, pattern)
+        })
+    }
+
+    /// Adds a target of any kind. It stands on its own: a prefix given this way is
+    /// reported by itself rather than narrowing down the suffixes.
+    pub fn target(mut self, target: Target) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    /// Match targets in any letter case.
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// EVM only: match the EIP-55 checksummed address, letter case included.
+    pub fn eip55(mut self, eip55: bool) -> Self {
+        self.eip55 = eip55;
+        self
+    }
+
+    /// Results wanted when no target has a quota. Defaults to 1.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Worker threads. Defaults to one per CPU core.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    pub fn strategy(mut self, strategy: KeyStrategy) -> Self {
+        self.keys.strategy = strategy;
+        self
+    }
+
+    /// Words per seed phrase for [`KeyStrategy::Mnemonic`]: 12, 15, 18, 21 or 24.
+    pub fn mnemonic_words(mut self, words: usize) -> Self {
+        self.keys.mnemonic_words = words;
+        self
+    }
+
+    /// Address indices checked per seed phrase for [`KeyStrategy::Mnemonic`].
+    pub fn mnemonic_indices(mut self, indices: u32) -> Self {
+        self.keys.mnemonic_indices = indices;
+        self
+    }
+
+    /// Searches offsets from `base` instead of private keys, see [`crate::split_key`].
+    pub fn split_key(mut self, base: PublicKey) -> Self {
+        self.split_key = Some(base);
+        self
+    }
+
+    /// Stops the search after this long.
+    pub fn max_time(mut self, max_time: Duration) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+
+    /// Stops the search after this many attempts, overshooting by less than one
+    /// batch per thread, see [`Attempts`].
+    pub fn max_attempts(mut self, max_attempts: u64) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Runs the (experimental) GPU worker instead of the CPU threads. Needs the
+    /// `gpu` feature.
+    pub fn gpu(mut self, gpu: bool) -> Self {
+        self.gpu = gpu;
+        self
+    }
+
+    /// Ranks every address with `scorer` instead of matching targets, keeping the
+    /// best `top`; see [`crate::score`]. Only addresses that make the leaderboard
+    /// are handed out, and the search runs until it is stopped or out of budget.
+    pub fn score(mut self, scorer: impl Scorer + 'static, top: usize) -> Self {
+        self.scorer = Some(Arc::new(scorer));
+        self.top = top;
+        self
+    }
+
+    /// Checks the configuration, rejecting targets no address can match (they would
+    /// search forever) and option combinations that make no sense.
+    pub fn build(self) -> Result<Search, String> {
+        let has_targets = !self.combined.is_empty() || !self.targets.is_empty();
+        if !has_targets && self.scorer.is_none() {
+            return Err("Nothing to search for: give a prefix, suffix or pattern".to_string());
+        }
+        if self.scorer.is_some() && has_targets {
+            return Err(
+                "--score ranks every address and cannot be combined with prefixes, suffixes or patterns"
+                    .to_string(),
+            );
+        }
+        // Plain hex addresses have no meaningful case.
+        let folds_case = self.chain.folds_case(self.eip55);
+        let ignore_case = self.ignore_case || folds_case;
+
+        let (mut prefixes, mut suffixes) = (Vec::new(), Vec::new());
+        let (mut narrowing, mut narrowed) = (Vec::new(), Vec::new());
+        let (mut contains, mut patterns) = (Vec::new(), Vec::new());
+        let combined = self.combined.into_iter().map(|target| (target, true));
+        let standalone = self.targets.into_iter().map(|target| (target, false));
+        for (target, combines) in combined.chain(standalone) {
+            if target.positions.is_some() && target.kind != TargetKind::Contains {
+                return Err(format!(
+                    "\"{}\": position ranges only apply to substrings (--contains)",
+                    target.value
+                ));
+            }
+            let own_case = target
+                .ignore_case
+                .map_or(ignore_case, |own| own || folds_case);
+            let mut entry = Entry {
+                name: target.value.clone(),
+                value: target.value,
+                quota: target.quota,
+                label: target.label,
+                positions: target.positions,
+                ignore_case: own_case,
+            };
+            match target.kind {
+                TargetKind::Prefix if combines => narrowing.push(entry),
+                TargetKind::Prefix => prefixes.push(entry),
+                TargetKind::Suffix if combines => narrowed.push(entry),
+                TargetKind::Suffix => suffixes.push(entry),
+                TargetKind::Contains => {
+                    entry.name = Contains::new(&entry.value, entry.positions.clone()).to_string();
+                    contains.push(entry);
+                }
+                TargetKind::Pattern => {
+                    if own_case != ignore_case {
+                        entry.value = with_case(&entry.value, own_case);
+                    }
+                    patterns.push(entry);
+                }
+            }
+        }
+
+        // `--prefixes` only narrow down `--suffixes` when both are given; results
+        // count towards the suffix, so that's where quotas belong. Otherwise they
+        // are targets of their own.
+        if narrowed.is_empty() {
+            prefixes.splice(0..0, narrowing.drain(..));
+        } else if narrowing.iter().any(|e| e.quota.is_some()) {
+            return Err(
+                "Quotas on --prefixes are only allowed without --suffixes; put them on the suffixes instead"
+                    .to_string(),
+            );
+        }
+        let narrowed_suffixes = if narrowing.is_empty() {
+            0
+        } else {
+            narrowed.len()
+        };
+        narrowed.append(&mut suffixes);
+        let suffixes = narrowed;
+        let prefix_values = prefixes.iter().map(|e| e.value.clone()).collect();
+        let suffix_values = suffixes.iter().map(|e| e.value.clone()).collect();
+        let narrowing_values = narrowing.into_iter().map(|e| e.value).collect();
+        let overrides = |entries: &[Entry]| -> Vec<usize> {
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.ignore_case != ignore_case)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        let case_overrides = CaseOverrides {
+            prefixes: overrides(&prefixes),
+            suffixes: overrides(&suffixes),
+            contains: overrides(&contains),
+        };
+        let contains_values = contains
+            .iter()
+            .map(|e| Contains::new(&e.value, e.positions.clone()))
+            .collect();
+        let pattern_values = patterns.iter().map(|e| e.value.clone()).collect();
+        let (mut names, mut labels, mut quotas) = (Vec::new(), Vec::new(), Vec::new());
+        for entry in prefixes
+            .into_iter()
+            .chain(suffixes)
+            .chain(contains)
+            .chain(patterns)
+        {
+            names.push(entry.name);
+            labels.push(entry.label);
+            quotas.push(entry.quota);
+        }
+        if self.scorer.is_some() {
+            // A single stand-in target that every candidate counts towards.
+            names.push("score".to_string());
+            labels.push(None);
+            quotas.push(None);
+        }
+
+        if self.eip55 && self.chain != Chain::Evm {
+            return Err("--eip55 only applies to --chain evm".to_string());
+        }
+        if self.eip55 && self.ignore_case {
+            return Err(
+                "--eip55 matches letter case exactly and cannot be combined with --ignore-case"
+                    .to_string(),
+            );
+        }
+        if self.keys.strategy == KeyStrategy::Mnemonic && self.split_key.is_some() {
+            return Err("--strategy mnemonic cannot be combined with --split-key".to_string());
+        }
+        if ![12, 15, 18, 21, 24].contains(&self.keys.mnemonic_words) {
+            return Err("Seed phrases have 12, 15, 18, 21 or 24 words".to_string());
+        }
+        if self.keys.mnemonic_indices == 0
+            || self.count == 0
+            || self.threads == Some(0)
+            || self.top == 0
+        {
+            return Err(
+                "Mnemonic indices, count, threads and --top must be at least 1".to_string(),
+            );
+        }
+        if self.gpu {
+            if !cfg!(feature = "gpu") {
+                return Err("This build does not include the GPU worker".to_string());
+            }
+            if self.chain != Chain::Tron {
+                return Err("GPU mode only supports --chain tron.".to_string());
+            }
+            if self.split_key.is_some() {
+                return Err("GPU mode does not support --split-key.".to_string());
+            }
+            if self.keys.strategy == KeyStrategy::Mnemonic {
+                return Err("GPU mode does not support --strategy mnemonic.".to_string());
+            }
+            if self.scorer.is_some() {
+                return Err("GPU mode does not support --score.".to_string());
+            }
+        }
+
+        let targets = SearchTargets {
+            prefixes: prefix_values,
+            suffixes: suffix_values,
+            narrowing_prefixes: narrowing_values,
+            narrowed_suffixes,
+            contains: contains_values,
+            patterns: pattern_values,
+            ignore_case,
+            case_overrides,
+            chain: self.chain,
+            eip55: self.eip55,
+            quotas,
+            split_key: self.split_key,
+            score: self
+                .scorer
+                .map(|scorer| ScoreTarget::new(scorer, folds_case)),
+        };
+        for (i, prefix) in targets.prefixes.iter().enumerate() {
+            validate_prefix(prefix, targets.chain, targets.prefix_ignores_case(i))
+                .map_err(|e| e.to_string())?;
+        }
+        for (i, suffix) in targets.suffixes.iter().enumerate() {
+            validate_suffix(suffix, targets.chain, targets.suffix_ignores_case(i))
+                .map_err(|e| e.to_string())?;
+        }
+        for prefix in &targets.narrowing_prefixes {
+            validate_prefix(prefix, targets.chain, targets.ignore_case)
+                .map_err(|e| e.to_string())?;
+        }
+        let warnings = validate_affix_lengths(
+            &targets.narrowing_prefixes,
+            &targets.suffixes[..targets.narrowed_suffixes],
+            targets.chain,
+        )
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|pair| format!("{}; that pair is skipped", pair))
+        .collect();
+        for (i, within) in targets.contains.iter().enumerate() {
+            let ignore_case = targets.contains_ignores_case(i);
+            validate_substring(&within.text, targets.chain, ignore_case)
+                .map_err(|e| e.to_string())?;
+            // Zero where it never fits, or only fits where the chain's first
+            // characters cannot spell it.
+            if contains_probability(&targets, within, ignore_case) == 0.0 {
+                return Err(format!(
+                    "\"{}\" can never appear in a {} address at those positions",
+                    within,
+                    targets.chain.name()
+                ));
+            }
+        }
+        for pattern in &targets.patterns {
+            AddressPattern::compile(pattern, targets.ignore_case)
+                .map_err(|e| format!("Invalid pattern \"{}\": {}", pattern, e))?;
+            check_reachable(pattern, targets.chain, targets.ignore_case)?;
+        }
+
+        Ok(Search {
+            targets,
+            names,
+            labels,
+            keys: self.keys,
+            count: self.count,
+            threads: self.threads.unwrap_or_else(num_cpus::get),
+            max_time: self.max_time,
+            max_attempts: self.max_attempts,
+            gpu: self.gpu,
+            top: self.top,
+            warnings,
+        })
+    }
+}
+
+/// A checked search configuration, ready to [`start`](Self::start).
+pub struct Search {
+    targets: SearchTargets,
+    /// What each target is reported as, in index order.
+    names: Vec<String>,
+    labels: Vec<Option<String>>,
+    keys: KeyOptions,
+    count: usize,
+    threads: usize,
+    max_time: Option<Duration>,
+    max_attempts: Option<u64>,
+    gpu: bool,
+    top: usize,
+    warnings: Vec<String>,
+}
+
+impl Search {
+    pub fn targets(&self) -> &SearchTargets {
+        &self.targets
+    }
+
+    pub fn keys(&self) -> KeyOptions {
+        self.keys
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Parts of the targets that are left out, e.g. prefix and suffix pairs too
+    /// long to share an address.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Number of results the search is after; unbounded (`usize::MAX`) in score mode.
+    pub fn total(&self) -> usize {
+        self.quota_tracker().total()
+    }
+
+    /// Leaderboard size, in score mode.
+    pub fn top(&self) -> Option<usize> {
+        self.targets.score.as_ref().map(|_| self.top)
+    }
+
+    fn quota_tracker(&self) -> QuotaTracker {
+        let count = match self.targets.score {
+            Some(_) => usize::MAX,
+            None => self.count,
+        };
+        QuotaTracker::new(&self.targets.quotas, count)
+    }
+
+    /// Starts the workers in the background. Results are collected by iterating
+    /// over the returned handle.
+    pub fn start(self) -> SearchHandle {
+        let (sender, receiver) = mpsc::channel();
+        let stop = StopHandle(Arc::new(AtomicBool::new(false)));
+        let progress = Progress {
+            attempts: Arc::new(AtomicU64::new(0)),
+            started: Instant::now(),
+        };
+        let quotas = self.quota_tracker();
+        let scoring = self
+            .targets
+            .score
+            .clone()
+            .map(|score| (score, Leaderboard::new(self.top)));
+        let chain = self.targets.chain;
+        let split_key = self.targets.split_key;
+
+        // The workers enforce the attempt budget themselves, after every batch.
+        let out_of_time = Arc::new(AtomicBool::new(false));
+        if let Some(max_time) = self.max_time {
+            let (stop, progress) = (stop.clone(), progress.clone());
+            let out_of_time = Arc::clone(&out_of_time);
+            thread::spawn(move || {
+                while !stop.is_stopped() {
+                    if progress.elapsed() >= max_time {
+                        out_of_time.store(true, Ordering::Relaxed);
+                        stop.stop();
+                        break;
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                }
+            });
+        }
+
+        let filled = quotas.filled();
+        let should_stop = Arc::clone(&stop.0);
+        let attempts = Attempts::new(Arc::clone(&progress.attempts), self.max_attempts);
+        let worker = if self.gpu {
+            spawn_gpu_worker(self.targets, sender, should_stop, filled, attempts)
+        } else {
+            let (targets, keys, threads) = (self.targets, self.keys, self.threads);
+            thread::spawn(move || {
+                // A pool of our own, so that searches don't depend on (or take over)
+                // the global rayon pool of the embedding program.
+                let pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .expect("failed to start the worker threads");
+                pool.install(|| {
+                    worker::search(
+                        targets,
+                        keys,
+                        sender,
+                        &should_stop,
+                        &filled,
+                        &attempts,
+                        threads,
+                    )
+                });
+            })
+        };
+
+        SearchHandle {
+            receiver,
+            quotas,
+            names: self.names,
+            labels: self.labels,
+            scoring,
+            chain,
+            split_key,
+            stop,
+            progress,
+            out_of_time,
+            max_attempts: self.max_attempts,
+            discarded: 0,
+            worker: Some(worker),
+        }
+    }
+}
+
+/// The GPU worker is an async function, so pollster blocks on it on its own thread.
+#[cfg(feature = "gpu")]
+fn spawn_gpu_worker(
+    targets: SearchTargets,
+    sender: Sender<FoundWallet>,
+    should_stop: Arc<AtomicBool>,
+    filled: Arc<[AtomicBool]>,
+    attempts: Attempts,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        pollster::block_on(crate::gpu_worker::search(
+            targets,
+            sender,
+            &should_stop,
+            &filled,
+            &attempts,
+        ))
+    })
+}
+
+#[cfg(not(feature = "gpu"))]
+fn spawn_gpu_worker(
+    _targets: SearchTargets,
+    _sender: Sender<FoundWallet>,
+    _should_stop: Arc<AtomicBool>,
+    _filled: Arc<[AtomicBool]>,
+    _attempts: Attempts,
+) -> JoinHandle<()> {
+    unreachable!("GPU searches are rejected by SearchBuilder::build without the gpu feature")
+}
+
+/// Stops a running search from anywhere, e.g. a signal handler. Results already
+/// found are still handed out by the [`SearchHandle`].
+#[derive(Clone, Debug)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Live attempt counter of a running search, cheap to clone into other threads.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    attempts: Arc<AtomicU64>,
+    started: Instant,
+}
+
+impl Progress {
+    /// Keys checked so far, across all workers.
+    pub fn attempts(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// A snapshot of how far a search got.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchStats {
+    pub attempts: u64,
+    pub elapsed: Duration,
+    /// Results handed out so far.
+    pub found: usize,
+    /// Results the search is after.
+    pub total: usize,
+    /// Results dropped because their key did not derive their address. This
+    /// indicates a bug and should always be 0.
+    pub discarded: usize,
+}
+
+impl SearchStats {
+    /// Average attempts per second.
+    pub fn speed(&self) -> f64 {
+        self.attempts as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Why a search ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// Every result asked for was found.
+    Completed,
+    /// [`StopHandle::stop`] was called.
+    Stopped,
+    TimeLimit,
+    AttemptLimit,
+    /// The workers exited on their own, which means they failed.
+    Aborted,
+}
+
+/// A verified result.
+pub struct SearchResult {
+    pub wallet: FoundWallet,
+    /// The public key the address belongs to: the key's own, or for split-key
+    /// searches the base key plus the offset.
+    pub public_key: PublicKey,
+    /// The target the address matched, as given to the builder.
+    pub pattern: String,
+    /// The matched target's [`Target::label`].
+    pub label: Option<String>,
+    /// In score mode, what the address scored.
+    pub score: Option<u32>,
+    /// Keys checked so far when the result was handed out.
+    pub attempts: u64,
+}
+
+/// A running search. Iterating yields each result as it is found, and ends once the
+/// workers have stopped (after completing, running out of budget or being stopped)
+/// and every result they sent has been handed out.
+pub struct SearchHandle {
+    receiver: Receiver<FoundWallet>,
+    quotas: QuotaTracker,
+    names: Vec<String>,
+    labels: Vec<Option<String>>,
+    scoring: Option<(ScoreTarget, Leaderboard)>,
+    chain: Chain,
+    split_key: Option<PublicKey>,
+    stop: StopHandle,
+    progress: Progress,
+    out_of_time: Arc<AtomicBool>,
+    max_attempts: Option<u64>,
+    discarded: usize,
+    /// Taken once the workers have finished.
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SearchHandle {
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress.clone()
+    }
+
+    /// The best addresses so far, in score mode.
+    pub fn leaderboard(&self) -> Option<&Leaderboard> {
+        self.scoring.as_ref().map(|(_, board)| board)
+    }
+
+    pub fn stats(&self) -> SearchStats {
+        SearchStats {
+            attempts: self.progress.attempts(),
+            elapsed: self.progress.elapsed(),
+            found: self.quotas.found(),
+            total: self.quotas.total(),
+            discarded: self.discarded,
+        }
+    }
+
+    /// Why the search ended, once iterating has returned `None`.
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        if self.worker.is_some() {
+            None
+        } else if self.quotas.is_complete() {
+            Some(StopReason::Completed)
+        } else if self
+            .max_attempts
+            .is_some_and(|max| self.progress.attempts() >= max)
+        {
+            Some(StopReason::AttemptLimit)
+        } else if self.out_of_time.load(Ordering::Relaxed) {
+            Some(StopReason::TimeLimit)
+        } else if self.stop.is_stopped() {
+            Some(StopReason::Stopped)
+        } else {
+            Some(StopReason::Aborted)
+        }
+    }
+
+    /// Re-derives the address from the private key (or split-key offset) the slow,
+    /// obvious way, and returns its public key if it is the one found. The search
+    /// fast paths (key walking, residue matching) never build it like this. For
+    /// mnemonic hits, the key must also follow from the phrase.
+    fn verified_public_key(&self, found: &FoundWallet) -> Option<PublicKey> {
+        found
+            .secret
+            .to_secret_key()
+            .filter(|key| match &found.mnemonic {
+                Some(origin) => origin.secret_key().as_ref() == Some(key),
+                None => true,
+            })
+            .and_then(|key| match &self.split_key {
+                Some(base) => offset_public_key(base, &key).ok(),
+                None => Some(key.public_key(secp256k1::SECP256K1)),
+            })
+            .filter(|public_key| {
+                let address = match self.chain {
+                    Chain::Tron => crate::address::public_key_to_tron_address(public_key),
+                    chain => chain.address_from_uncompressed(&public_key.serialize_uncompressed()),
+                };
+                address == found.address
+            })
+    }
+}
+
+impl Iterator for SearchHandle {
+    type Item = SearchResult;
+
+    fn next(&mut self) -> Option<SearchResult> {
+        for found in self.receiver.iter() {
+            let Some(public_key) = self.verified_public_key(&found) else {
+                self.discarded += 1;
+                continue;
+            };
+            let score = match &mut self.scoring {
+                Some((target, board)) => {
+                    let score = target.score(&found.address[self.chain.lead().len()..]);
+                    // Workers report against a threshold that may be out of date.
+                    if board.insert(score, &found.address).is_none() {
+                        continue;
+                    }
+                    target.raise_threshold(board);
+                    Some(score)
+                }
+                None => None,
+            };
+            // Another thread may have filled this target's quota in the meantime.
+            if !self.quotas.accept(found.target) {
+                continue;
+            }
+            if self.quotas.is_complete() {
+                self.stop.stop();
+            }
+            return Some(SearchResult {
+                pattern: self.names[found.target].clone(),
+                label: self.labels[found.target].clone(),
+                score,
+                attempts: self.progress.attempts(),
+                public_key,
+                wallet: found,
+            });
+        }
+        // Every sender is gone, so the workers are done; let the pool wind down.
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        None
+    }
+}
+
+impl Drop for SearchHandle {
+    /// Dropping the handle early stops the workers instead of leaving them running.
+    fn drop(&mut self) {
+        self.stop.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns_file::{self, FileFormat};
+    use crate::walk::WALK_BATCH;
+
+    #[test]
+    fn test_quotas_and_stats() {
+        let mut handle = SearchBuilder::new(Chain::Evm)
+            .suffix_with_quota("a", Some(2))
+            .pattern("glob:0x1*")
+            .strategy(KeyStrategy::Walk)
+            .threads(2)
+            .build()
+            .unwrap()
+            .start();
+        let results: Vec<SearchResult> = handle.by_ref().collect();
+
+        assert_eq!(results.len(), 3);
+        let suffix_hits = results.iter().filter(|r| r.pattern == "a").count();
+        assert_eq!(suffix_hits, 2);
+        for result in &results {
+            assert_eq!(
+                result.wallet.address,
+                Chain::Evm.address_from_uncompressed(&result.public_key.serialize_uncompressed())
+            );
+        }
+        assert_eq!(handle.stop_reason(), Some(StopReason::Completed));
+        let stats = handle.stats();
+        assert_eq!((stats.found, stats.total, stats.discarded), (3, 3, 0));
+    }
+
+    #[test]
+    fn test_targets_with_labels_and_own_case() {
+        let labelled = |kind, value: &str, ignore_case, label: &str| Target {
+            ignore_case,
+            quota: Some(1),
+            label: Some(label.to_string()),
+            ..Target::new(kind, value)
+        };
+        let search = SearchBuilder::new(Chain::Tron)
+            .target(labelled(TargetKind::Suffix, "a", None, "order-1"))
+            .target(labelled(TargetKind::Suffix, "B", Some(true), "order-2"))
+            .target(labelled(TargetKind::Contains, "c", None, "order-3"))
+            .build()
+            .unwrap();
+        assert_eq!(search.targets().suffixes, ["a", "B"]);
+        assert_eq!(search.targets().case_overrides.suffixes, [1]);
+        assert_eq!(search.targets().contains, [Contains::new("c", None)]);
+
+        let results: Vec<SearchResult> = search.start().collect();
+        assert_eq!(results.len(), 3);
+        for result in &results {
+            let address = &result.wallet.address;
+            match result.label.as_deref() {
+                Some("order-1") => assert!(address.ends_with('a')),
+                Some("order-2") => assert!(address.to_lowercase().ends_with('b')),
+                Some("order-3") => assert!(address.contains('c')),
+                label => panic!("unexpected label {:?}", label),
+            }
+        }
+        assert!(results.iter().any(|r| r.pattern == "B"));
+
+        // Each target is checked against its own case handling.
+        assert!(SearchBuilder::new(Chain::Tron)
+            .target(labelled(TargetKind::Suffix, "l", Some(false), "x"))
+            .build()
+            .is_err());
+        assert!(SearchBuilder::new(Chain::Tron)
+            .target(labelled(TargetKind::Suffix, "l", Some(true), "x"))
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_order_file_lines_stand_alone() {
+        let lines = "prefix:R:2 order-1\n~b order-2\n";
+        let mut builder = SearchBuilder::new(Chain::Tron)
+            .prefix("S")
+            .suffix("a")
+            .threads(1);
+        for target in patterns_file::parse(lines, FileFormat::Text).unwrap() {
+            builder = builder.target(target);
+        }
+        let search = builder.build().unwrap();
+        let targets = search.targets();
+        assert_eq!(targets.prefixes, ["R"]);
+        assert_eq!(targets.suffixes, ["a", "b"]);
+        // Only the builder's own prefix and suffix combine.
+        assert_eq!(targets.narrowing_prefixes, ["S"]);
+        assert_eq!(targets.narrowed_suffixes, 1);
+        assert_eq!(targets.case_overrides.suffixes, [1]);
+        assert_eq!(search.total(), 4);
+
+        let results: Vec<SearchResult> = search.start().collect();
+        assert_eq!(results.len(), 4);
+        for result in &results {
+            let address = &result.wallet.address;
+            match (result.pattern.as_str(), result.label.as_deref()) {
+                ("R", Some("order-1")) => assert!(address[1..].starts_with('R')),
+                ("b", Some("order-2")) => assert!(address.to_lowercase().ends_with('b')),
+                ("a", None) => assert!(address[1..].starts_with('S') && address.ends_with('a')),
+                target => panic!("unexpected target {:?}", target),
+            }
+        }
+        let order_1 = results.iter().filter(|r| r.pattern == "R").count();
+        assert_eq!(order_1, 2);
+    }
+
+    #[test]
+    fn test_contains_within_positions() {
+        let search = SearchBuilder::new(Chain::Tron)
+            .target(Target {
+                quota: Some(2),
+                positions: Some(5..20),
+                ..Target::new(TargetKind::Contains, "x")
+            })
+            .target(Target {
+                ignore_case: Some(true),
+                positions: Some(30..usize::MAX),
+                ..Target::new(TargetKind::Contains, "k")
+            })
+            .build()
+            .unwrap();
+        assert_eq!(search.targets().contains.len(), 2);
+        assert_eq!(search.targets().case_overrides.contains, [1]);
+        assert!(search.targets().patterns.is_empty());
+
+        let results: Vec<SearchResult> = search.start().collect();
+        assert_eq!(results.len(), 3);
+        for result in &results {
+            let body = &result.wallet.address[1..];
+            match result.pattern.as_str() {
+                "x@5..20" => assert!(body[5..].find('x').is_some_and(|i| i < 15)),
+                "k@30.." => assert!(body[30..].to_lowercase().contains('k')),
+                pattern => panic!("unexpected target {:?}", pattern),
+            }
+        }
+
+        // Substrings that never fit, or whose position can't hold them.
+        assert!(SearchBuilder::new(Chain::Tron)
+            .target(Target {
+                positions: Some(40..usize::MAX),
+                ..Target::new(TargetKind::Contains, "x")
+            })
+            .build()
+            .is_err());
+        assert!(SearchBuilder::new(Chain::Tron)
+            .target(Target {
+                positions: Some(0..1),
+                ..Target::new(TargetKind::Contains, "a")
+            })
+            .build()
+            .is_err());
+        assert!(SearchBuilder::new(Chain::Tron)
+            .target(Target {
+                positions: Some(0..1),
+                ..Target::new(TargetKind::Suffix, "a")
+            })
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_score_mode_keeps_a_leaderboard() {
+        let ones = |body: &str| body.bytes().filter(|&b| b == b'1').count() as u32;
+        let mut handle = SearchBuilder::new(Chain::Tron)
+            .score(ones, 3)
+            .threads(1)
+            .max_attempts(5000)
+            .build()
+            .unwrap()
+            .start();
+        let results: Vec<SearchResult> = handle.by_ref().collect();
+        assert_eq!(handle.stop_reason(), Some(StopReason::AttemptLimit));
+
+        // Every result made the board when it was found, scored as the workers saw it.
+        assert!(results.len() >= 3);
+        for result in &results {
+            assert_eq!(result.score, Some(ones(&result.wallet.address[1..])));
+        }
+        let board = handle.leaderboard().unwrap().entries();
+        assert_eq!(board.len(), 3);
+        assert!(board.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(results.iter().all(|r| r.score.unwrap() <= board[0].score));
+
+        assert!(SearchBuilder::new(Chain::Tron)
+            .suffix("a")
+            .score(ones, 3)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_stop_and_budgets() {
+        let mut handle = SearchBuilder::new(Chain::Tron)
+            .suffix("zzzzzzzzzz")
+            .threads(1)
+            .build()
+            .unwrap()
+            .start();
+        assert_eq!(handle.stop_reason(), None);
+        handle.stop_handle().stop();
+        assert!(handle.next().is_none());
+        assert_eq!(handle.stop_reason(), Some(StopReason::Stopped));
+
+        let mut handle = SearchBuilder::new(Chain::Tron)
+            .suffix("zzzzzzzzzz")
+            .threads(1)
+            .max_attempts(1)
+            .build()
+            .unwrap()
+            .start();
+        assert!(handle.next().is_none());
+        assert_eq!(handle.stop_reason(), Some(StopReason::AttemptLimit));
+
+        // Each thread overshoots by less than one batch.
+        let mut handle = SearchBuilder::new(Chain::Tron)
+            .suffix("zzzzzzzzzz")
+            .strategy(KeyStrategy::Walk)
+            .threads(2)
+            .max_attempts(20_000)
+            .build()
+            .unwrap()
+            .start();
+        assert!(handle.next().is_none());
+        assert_eq!(handle.stop_reason(), Some(StopReason::AttemptLimit));
+        assert!(handle.stats().attempts < 20_000 + 2 * WALK_BATCH as u64);
+    }
+
+    #[test]
+    fn test_prefix_and_suffix_pairs_that_do_not_fit() {
+        let (long_prefix, long_suffix) = ("R".repeat(21), "a".repeat(15));
+        let search = SearchBuilder::new(Chain::Tron)
+            .prefix("RX")
+            .prefix(long_prefix.clone())
+            .suffix("a")
+            .suffix(long_suffix.clone())
+            .build()
+            .unwrap();
+        assert_eq!(search.warnings().len(), 1);
+        assert!(search.warnings()[0].contains(&long_prefix));
+
+        assert!(SearchBuilder::new(Chain::Tron)
+            .prefix(long_prefix)
+            .suffix(long_suffix)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_build_rejects_bad_configurations() {
+        assert!(SearchBuilder::new(Chain::Tron).build().is_err());
+        assert!(SearchBuilder::new(Chain::Tron).suffix("0").build().is_err());
+        assert!(SearchBuilder::new(Chain::Tron)
+            .suffix("a")
+            .eip55(true)
+            .build()
+            .is_err());
+        assert!(SearchBuilder::new(Chain::Tron)
+            .suffix("a")
+            .prefix_with_quota("R", Some(2))
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! src/secret.rs
+
+use secp256k1::SecretKey;
+use std::fmt;
+use zeroize::Zeroize;
+
+/// The 32 bytes of a found private key (or split-key offset).
+///
+/// The bytes live in their own heap allocation, which is locked into RAM where the
+/// platform allows it (so it is never written to swap) and zeroed on drop. Locks
+/// are per page and don't nest, so the page stays locked after the drop: unlocking
+/// it would also unlock any other secret sharing it.
+///
+/// There is no `Display`, `Debug` is redacted, and the only way to the bytes is the
+/// explicit [`Self::expose`], which only [`crate::output::WalletRecord`] calls for
+/// rendering.
+pub struct SecretBytes(Box<[u8; 32]>);
+
+impl SecretBytes {
+    pub fn new(mut bytes: [u8; 32]) -> Self {
+        let secret = Self(Box::new(bytes));
+        bytes.zeroize();
+        lock_memory(&secret.0[..]);
+        secret
+    }
+
+    /// The raw secret. Everything that renders it (hex, WIF) must zeroize the result.
+    pub fn expose(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// The secret as a key, or `None` if it is out of range (it never is for keys
+    /// produced by the search).
+    pub fn to_secret_key(&self) -> Option<SecretKey> {
+        SecretKey::from_slice(&self.0[..]).ok()
+    }
+}
+
+impl From<&SecretKey> for SecretBytes {
+    fn from(key: &SecretKey) -> Self {
+        Self::new(key.secret_bytes())
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretBytes(<redacted>)")
+    }
+}
+
+/// Best effort: failing to lock (e.g. over `RLIMIT_MEMLOCK`) is not an error.
+#[cfg(unix)]
+fn lock_memory(bytes: &[u8]) {
+    // SAFETY: the range is a live allocation owned by the caller.
+    unsafe {
+        libc::mlock(bytes.as_ptr().cast(), bytes.len());
+    }
+}
+
+#[cfg(not(unix))]
+fn lock_memory(_bytes: &[u8]) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let secret = SecretBytes::from(&key);
+        assert_eq!(format!("{:?}", secret), "SecretBytes(<redacted>)");
+        assert_eq!(secret.to_secret_key(), Some(key));
+    }
+}
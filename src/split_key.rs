@@ -0,0 +1,37 @@
+//! src/split_key.rs
+
+use secp256k1::{Error, PublicKey, Scalar, SecretKey, SECP256K1};
+
+/// Split-key vanity generation, for searching on machines that must never learn
+/// the final private key.
+///
+/// The owner keeps a secret `s` and hands out only `P = s·G`. The searcher looks for
+/// an offset `t` such that `P + t·G` has a matching address and reports `t`, which
+/// is useless without `s`. The owner then combines the two into `s + t`, the private
+/// key of `P + t·G`.
+///
+/// Returns the public key `base + offset·G` of a candidate offset.
+pub fn offset_public_key(base: &PublicKey, offset: &SecretKey) -> Result<PublicKey, Error> {
+    base.add_exp_tweak(SECP256K1, &Scalar::from(*offset))
+}
+
+/// Adds an offset found by a split-key search to the owner's secret key.
+pub fn combine(secret: &SecretKey, offset: &SecretKey) -> Result<SecretKey, Error> {
+    secret.add_tweak(&Scalar::from(*offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combined_key_matches_offset_point() {
+        let mut rng = secp256k1::rand::thread_rng();
+        let secret = SecretKey::new(&mut rng);
+        let offset = SecretKey::new(&mut rng);
+
+        let searched = offset_public_key(&secret.public_key(SECP256K1), &offset).unwrap();
+        let combined = combine(&secret, &offset).unwrap();
+        assert_eq!(combined.public_key(SECP256K1), searched);
+    }
+}
@@ -0,0 +1,328 @@
+//! src/validate.rs
+
+use crate::address::{is_possible_tron_prefix, tron_second_chars};
+use crate::chain::Chain;
+use std::fmt;
+
+/// Why a prefix or suffix can never be found.
+#[derive(Debug, PartialEq)]
+pub enum PatternError {
+    Empty {
+        kind: &'static str,
+    },
+    TooLong {
+        kind: &'static str,
+        chain: &'static str,
+        pattern: String,
+        max: usize,
+    },
+    InvalidChar {
+        kind: &'static str,
+        chain: &'static str,
+        pattern: String,
+        index: usize,
+        found: char,
+        suggestion: Option<char>,
+    },
+    ImpossiblePrefix {
+        pattern: String,
+        second_chars: String,
+    },
+    /// A prefix and a suffix that together need more characters than there are.
+    Overlapping {
+        chain: &'static str,
+        prefix: String,
+        suffix: String,
+        max: usize,
+    },
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Empty { kind } => {
+                write!(f, "Empty {} (check for a stray comma)", kind)
+            }
+            PatternError::TooLong {
+                kind,
+                chain,
+                pattern,
+                max,
+            } => write!(
+                f,
+                "{} \"{}\" is {} characters long, but at most {} fit in a {} address",
+                capitalize(kind),
+                pattern,
+                pattern.chars().count(),
+                max,
+                chain
+            ),
+            PatternError::InvalidChar {
+                kind,
+                chain,
+                pattern,
+                index,
+                found,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "{} \"{}\" contains '{}', which never appears in a {} address",
+                    capitalize(kind),
+                    pattern,
+                    found,
+                    chain
+                )?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, " (did you mean '{}'?)", suggestion)?;
+                }
+                // Point at the offending character underneath the quoted pattern.
+                write!(f, "\n    {}\n    {}^", pattern, " ".repeat(*index))
+            }
+            PatternError::ImpossiblePrefix {
+                pattern,
+                second_chars,
+            } => write!(
+                f,
+                "Prefix \"{}\" is impossible: the character after `T` must be one of {} and the address must stay between the lowest and highest Tron addresses",
+                pattern, second_chars
+            ),
+            PatternError::Overlapping {
+                chain,
+                prefix,
+                suffix,
+                max,
+            } => write!(
+                f,
+                "Prefix \"{}\" and suffix \"{}\" need {} characters together, but only {} fit in a {} address",
+                prefix,
+                suffix,
+                prefix.chars().count() + suffix.chars().count(),
+                max,
+                chain
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+fn capitalize(kind: &str) -> String {
+    let mut chars = kind.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Checks whether `c` can occur in an address, folding case when requested.
+fn is_valid_char(chain: Chain, c: char, ignore_case: bool) -> bool {
+    chain.alphabet().iter().any(|&a| {
+        let a = a as char;
+        if ignore_case {
+            a.eq_ignore_ascii_case(&c)
+        } else {
+            a == c
+        }
+    })
+}
+
+/// Rejects empty, over-long patterns and ones using characters outside the
+/// chain's alphabet (Base58 for Tron, hex for EVM).
+fn validate_chars(
+    kind: &'static str,
+    pattern: &str,
+    chain: Chain,
+    ignore_case: bool,
+) -> Result<(), PatternError> {
+    let max = chain.address_len() - chain.lead().len();
+    if pattern.is_empty() {
+        return Err(PatternError::Empty { kind });
+    }
+    if let Some((index, found)) = pattern
+        .chars()
+        .enumerate()
+        .find(|&(_, c)| !is_valid_char(chain, c, ignore_case))
+    {
+        return Err(PatternError::InvalidChar {
+            kind,
+            chain: chain.name(),
+            pattern: pattern.to_string(),
+            index,
+            found,
+            suggestion: chain
+                .look_alike(found)
+                .filter(|&c| is_valid_char(chain, c, ignore_case)),
+        });
+    }
+    if pattern.chars().count() > max {
+        return Err(PatternError::TooLong {
+            kind,
+            chain: chain.name(),
+            pattern: pattern.to_string(),
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Validates a suffix against the chain's alphabet and the address length.
+pub fn validate_suffix(suffix: &str, chain: Chain, ignore_case: bool) -> Result<(), PatternError> {
+    validate_chars("suffix", suffix, chain, ignore_case)
+}
+
+/// Validates a substring (see [`crate::contains`]) against the chain's alphabet and
+/// the address length.
+pub fn validate_substring(text: &str, chain: Chain, ignore_case: bool) -> Result<(), PatternError> {
+    validate_chars("substring", text, chain, ignore_case)
+}
+
+/// Validates a prefix (the characters after the lead, e.g. `T`) against the chain's
+/// alphabet and, for Tron, the range of values an address can take.
+pub fn validate_prefix(prefix: &str, chain: Chain, ignore_case: bool) -> Result<(), PatternError> {
+    validate_chars("prefix", prefix, chain, ignore_case)?;
+    if chain == Chain::Tron && !is_possible_tron_prefix(prefix, ignore_case) {
+        return Err(PatternError::ImpossiblePrefix {
+            pattern: prefix.to_string(),
+            second_chars: tron_second_chars().into_iter().collect(),
+        });
+    }
+    Ok(())
+}
+
+/// Every prefix is combined with every suffix, and a pair only fits if both fit
+/// side by side. Returns the pairs that don't, which the search skips, or an error
+/// when no pair fits at all.
+pub fn validate_affix_lengths(
+    prefixes: &[String],
+    suffixes: &[String],
+    chain: Chain,
+) -> Result<Vec<PatternError>, PatternError> {
+    let max = chain.address_len() - chain.lead().len();
+    let overlapping = |prefix: &String, suffix: &String| PatternError::Overlapping {
+        chain: chain.name(),
+        prefix: prefix.clone(),
+        suffix: suffix.clone(),
+        max,
+    };
+    let shortest = |affixes: &[String]| affixes.iter().min_by_key(|a| a.len()).cloned();
+    if let (Some(prefix), Some(suffix)) = (shortest(prefixes), shortest(suffixes)) {
+        if prefix.len() + suffix.len() > max {
+            return Err(overlapping(&prefix, &suffix));
+        }
+    }
+    Ok(prefixes
+        .iter()
+        .flat_map(|prefix| suffixes.iter().map(move |suffix| (prefix, suffix)))
+        .filter(|(prefix, suffix)| prefix.len() + suffix.len() > max)
+        .map(|(prefix, suffix)| overlapping(prefix, suffix))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_characters() {
+        assert_eq!(
+            validate_suffix("C0OL", Chain::Tron, false),
+            Err(PatternError::InvalidChar {
+                kind: "suffix",
+                chain: "Tron",
+                pattern: "C0OL".to_string(),
+                index: 1,
+                found: '0',
+                suggestion: Some('o'),
+            })
+        );
+        assert!(matches!(
+            validate_suffix("cool", Chain::Tron, false),
+            Err(PatternError::InvalidChar {
+                found: 'l',
+                suggestion: Some('L'),
+                ..
+            })
+        ));
+        // Case folding makes `l` and `O` reachable through `L` and `o`, but never `0`.
+        assert!(validate_suffix("cOOl", Chain::Tron, true).is_ok());
+        assert!(validate_suffix("c00L", Chain::Tron, true).is_err());
+        assert!(validate_suffix("", Chain::Tron, false).is_err());
+    }
+
+    #[test]
+    fn test_prefix_range() {
+        assert!(validate_prefix("RX", Chain::Tron, false).is_ok());
+        assert!(matches!(
+            validate_prefix("rx", Chain::Tron, false),
+            Err(PatternError::ImpossiblePrefix { .. })
+        ));
+        assert!(validate_prefix("rx", Chain::Tron, true).is_ok());
+        assert!(matches!(
+            validate_prefix(&"A".repeat(34), Chain::Tron, false),
+            Err(PatternError::TooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_affix_lengths() {
+        let strings = |affixes: &[&str]| affixes.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        let prefixes = strings(&["RX", &"R".repeat(21)]);
+        let suffixes = strings(&["a", &"a".repeat(15)]);
+        // Only the two long ones cannot share an address.
+        let skipped = validate_affix_lengths(&prefixes, &suffixes, Chain::Tron).unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert!(matches!(
+            &skipped[0],
+            PatternError::Overlapping { prefix, suffix, max: 33, .. }
+                if prefix.len() == 21 && suffix.len() == 15
+        ));
+        assert!(matches!(
+            validate_affix_lengths(&prefixes[1..], &suffixes[1..], Chain::Tron),
+            Err(PatternError::Overlapping { max: 33, .. })
+        ));
+        assert_eq!(
+            validate_affix_lengths(&prefixes, &[], Chain::Tron),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_evm_hex() {
+        assert!(validate_prefix("dEaD", Chain::Evm, false).is_ok());
+        assert!(validate_suffix("c0ffee", Chain::Evm, true).is_ok());
+        assert!(matches!(
+            validate_suffix("coffee", Chain::Evm, true),
+            Err(PatternError::InvalidChar {
+                found: 'o',
+                suggestion: Some('0'),
+                ..
+            })
+        ));
+        assert!(validate_prefix(&"a".repeat(41), Chain::Evm, true).is_err());
+    }
+
+    #[test]
+    fn test_bitcoin_alphabets() {
+        assert!(validate_prefix("Love", Chain::Btc, false).is_ok());
+        assert!(validate_suffix("C0OL", Chain::Btc, false).is_err());
+        // bech32 leaves out `1`, `b`, `i` and `o`.
+        assert!(validate_suffix("cafe", Chain::BtcSegwit, true).is_ok());
+        assert!(matches!(
+            validate_suffix("bob", Chain::BtcTaproot, true),
+            Err(PatternError::InvalidChar {
+                index: 0,
+                found: 'b',
+                ..
+            })
+        ));
+        assert!(matches!(
+            validate_prefix("c0o1", Chain::BtcSegwit, true),
+            Err(PatternError::InvalidChar {
+                found: 'o',
+                suggestion: Some('0'),
+                ..
+            })
+        ));
+    }
+}
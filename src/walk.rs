@@ -0,0 +1,129 @@
+//! src/walk.rs
+
+use k256::elliptic_curve::group::Curve;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::Field;
+use k256::{AffinePoint, ProjectivePoint, Scalar};
+use secp256k1::rand::RngCore;
+use zeroize::Zeroize;
+
+/// Number of consecutive keys normalized together with a single field inversion.
+pub const WALK_BATCH: usize = 1024;
+
+/// Walks consecutive public keys `k·G, (k+1)·G, (k+2)·G, ...` from a random `k`.
+/// With a base point `P` (split-key search) it walks `P + k·G, P + (k+1)·G, ...`
+/// instead, and the scalars it reports are offsets from `P`'s private key.
+///
+/// Each step is a single point addition instead of a full scalar multiplication,
+/// and the projective results are converted to affine coordinates a whole batch
+/// at a time (Montgomery's trick), so the expensive inversion is shared by
+/// `WALK_BATCH` keys. Private keys are only reconstructed for matches.
+pub struct KeyWalker {
+    /// Scalar of the first key in the current batch.
+    batch_start: Scalar,
+    /// The point following the last one in the current batch.
+    next: ProjectivePoint,
+    projective: Vec<ProjectivePoint>,
+    affine: Vec<AffinePoint>,
+}
+
+impl KeyWalker {
+    /// Starts a walk at a uniformly random, non-zero scalar.
+    pub fn new(rng: &mut impl RngCore) -> Self {
+        Self::offset_from(ProjectivePoint::IDENTITY, rng)
+    }
+
+    /// Starts a walk at `base` plus a uniformly random, non-zero multiple of `G`.
+    pub fn offset_from(base: ProjectivePoint, rng: &mut impl RngCore) -> Self {
+        let start = loop {
+            let candidate = Scalar::random(&mut *rng);
+            if !bool::from(candidate.is_zero()) {
+                break candidate;
+            }
+        };
+        Self::starting_at(base, start)
+    }
+
+    /// Starts a walk at `base + start·G`.
+    pub fn starting_at(base: ProjectivePoint, start: Scalar) -> Self {
+        Self {
+            // The first call to `advance` moves the start of the batch onto `start`.
+            batch_start: start - Scalar::from(WALK_BATCH as u64),
+            next: base + ProjectivePoint::GENERATOR * start,
+            projective: vec![ProjectivePoint::IDENTITY; WALK_BATCH],
+            affine: vec![AffinePoint::IDENTITY; WALK_BATCH],
+        }
+    }
+
+    /// Computes the next `WALK_BATCH` public keys.
+    pub fn advance(&mut self) {
+        for slot in self.projective.iter_mut() {
+            *slot = self.next;
+            self.next += AffinePoint::GENERATOR;
+        }
+        ProjectivePoint::batch_normalize(&self.projective, &mut self.affine);
+        self.batch_start += Scalar::from(WALK_BATCH as u64);
+    }
+
+    /// Uncompressed SEC1 encoding (`0x04 || X || Y`) of key `index` in the current batch.
+    #[inline]
+    pub fn public_key(&self, index: usize) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes.copy_from_slice(self.affine[index].to_encoded_point(false).as_bytes());
+        bytes
+    }
+
+    /// Private key (or offset from the base) of key `index` in the current batch, as
+    /// big-endian bytes.
+    pub fn secret_key(&self, index: usize) -> [u8; 32] {
+        (self.batch_start + Scalar::from(index as u64))
+            .to_bytes()
+            .into()
+    }
+}
+
+impl Drop for KeyWalker {
+    fn drop(&mut self) {
+        // Every private key of the walk follows from this one scalar.
+        self.batch_start.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Secp256k1, SecretKey};
+
+    #[test]
+    fn test_walk_matches_scalar_multiplication() {
+        let secp = Secp256k1::new();
+        let mut walker = KeyWalker::new(&mut secp256k1::rand::thread_rng());
+
+        for _ in 0..2 {
+            walker.advance();
+            for index in [0, 1, WALK_BATCH / 2, WALK_BATCH - 1] {
+                let secret = SecretKey::from_slice(&walker.secret_key(index)).unwrap();
+                let expected = secret.public_key(&secp).serialize_uncompressed();
+                assert_eq!(walker.public_key(index), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_walk_from_base_point() {
+        let secp = Secp256k1::new();
+        let mut rng = secp256k1::rand::thread_rng();
+        let (_, base) = secp.generate_keypair(&mut rng);
+        let base_point = k256::PublicKey::from_sec1_bytes(&base.serialize())
+            .unwrap()
+            .to_projective();
+
+        let mut walker = KeyWalker::offset_from(base_point, &mut rng);
+        walker.advance();
+        for index in [0, WALK_BATCH - 1] {
+            let offset = SecretKey::from_slice(&walker.secret_key(index)).unwrap();
+            let expected = crate::split_key::offset_public_key(&base, &offset).unwrap();
+            assert_eq!(walker.public_key(index), expected.serialize_uncompressed());
+        }
+    }
+}
@@ -1,116 +1,486 @@
 //! src/worker.rs
 
-use crate::address::public_key_to_tron_address;
-use secp256k1::{rand, Secp256k1};
+use crate::address::tron_payload_from_uncompressed;
+use crate::affix::AffixSet;
+use crate::chain::Chain;
+use crate::contains::{Contains, ContainsSet};
+use crate::evm::{evm_address_bytes, to_checksum_address, to_lower_address};
+use crate::mnemonic::{MnemonicKeys, MnemonicOrigin};
+use crate::pattern::AddressPattern;
+use crate::residue::ResidueMatcher;
+use crate::score::ScoreTarget;
+use crate::secret::SecretBytes;
+use crate::split_key::offset_public_key;
+use crate::walk::{KeyWalker, WALK_BATCH};
+use secp256k1::{rand, PublicKey, Secp256k1, SecretKey};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{mpsc::Sender, Arc};
 
 /// A struct to hold the result of a successful search.
 pub struct FoundWallet {
     pub address: String,
-    pub private_key_hex: String,
+    /// The private key, or in a split-key search the offset to combine with the
+    /// secret key instead.
+    pub secret: SecretBytes,
+    /// Index of the matched target, see [`SearchTargets::quotas`].
+    pub target: usize,
+    /// The seed phrase and path the key derives from, for mnemonic searches.
+    pub mnemonic: Option<MnemonicOrigin>,
 }
 
-struct SuffixGroup<'a> {
-    len: usize,
-    values: Vec<&'a [u8]>,
+/// Everything a search looks for, as given on the command line.
+#[derive(Clone)]
+pub struct SearchTargets {
+    /// Prefixes that are targets of their own.
+    pub prefixes: Vec<String>,
+    pub suffixes: Vec<String>,
+    /// `--prefixes` given together with `--suffixes`: the first `narrowed_suffixes`
+    /// suffixes only count when the address also starts with one of these. They
+    /// follow [`Self::ignore_case`].
+    pub narrowing_prefixes: Vec<String>,
+    pub narrowed_suffixes: usize,
+    /// Substrings wanted anywhere after the lead, or within a range of positions.
+    pub contains: Vec<Contains>,
+    pub patterns: Vec<String>,
+    /// Match prefixes, suffixes, substrings and patterns regardless of letter case.
+    pub ignore_case: bool,
+    /// Targets that decide letter case for themselves.
+    pub case_overrides: CaseOverrides,
+    /// Which address format to generate and match against.
+    pub chain: Chain,
+    /// EVM only: match against the EIP-55 checksummed address, letter case included.
+    pub eip55: bool,
+    /// Optional number of results wanted per target, in target index order:
+    /// prefixes, suffixes, substrings and patterns. Narrowing prefixes are not
+    /// targets; their results count towards the suffix.
+    pub quotas: Vec<Option<usize>>,
+    /// Split-key search: keys are offsets from this public key, see [`crate::split_key`].
+    pub split_key: Option<PublicKey>,
+    /// Score mode: rank every address instead of matching targets, see [`crate::score`].
+    pub score: Option<ScoreTarget>,
 }
 
-/// Pre-process suffixes into sorted buckets of bytes to minimize hot-loop overhead.
-fn group_suffixes_by_length(suffixes: &[String]) -> Vec<SuffixGroup<'_>> {
-    let mut groups = Vec::<SuffixGroup>::new();
-
-    for suffix in suffixes {
-        let len = suffix.len();
-        match groups.iter_mut().find(|g| g.len == len) {
-            Some(group) => group.values.push(suffix.as_bytes()),
-            None => groups.push(SuffixGroup {
-                len,
-                values: vec![suffix.as_bytes()],
-            }),
+/// Prefixes, suffixes and substrings matched with the opposite of
+/// [`SearchTargets::ignore_case`], by their index in their list. Patterns carry
+/// their own case in the expression.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CaseOverrides {
+    pub prefixes: Vec<usize>,
+    pub suffixes: Vec<usize>,
+    pub contains: Vec<usize>,
+}
+
+impl SearchTargets {
+    pub fn prefix_ignores_case(&self, index: usize) -> bool {
+        self.ignore_case != self.case_overrides.prefixes.contains(&index)
+    }
+
+    pub fn suffix_ignores_case(&self, index: usize) -> bool {
+        self.ignore_case != self.case_overrides.suffixes.contains(&index)
+    }
+
+    pub fn contains_ignores_case(&self, index: usize) -> bool {
+        self.ignore_case != self.case_overrides.contains.contains(&index)
+    }
+}
+
+/// How each thread produces candidate keys.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyStrategy {
+    /// A fresh random keypair (full scalar multiplication) per attempt.
+    #[default]
+    Random,
+    /// Random start per thread, then consecutive keys via point addition.
+    Walk,
+    /// A random BIP39 mnemonic per attempt, checking its first few HD addresses.
+    Mnemonic,
+}
+
+/// How candidate keys are generated.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyOptions {
+    pub strategy: KeyStrategy,
+    /// Mnemonic strategy: words per seed phrase.
+    pub mnemonic_words: usize,
+    /// Mnemonic strategy: address indices checked per seed phrase. Deriving the seed
+    /// is the expensive part, so checking several indices raises throughput.
+    pub mnemonic_indices: u32,
+}
+
+/// Targets pre-processed for the hot loop. Built once per thread so that regex
+/// patterns are compiled per thread rather than shared.
+pub(crate) struct Matcher<'a> {
+    /// Targets whose quota is already filled; matches for them are not reported.
+    filled: &'a [AtomicBool],
+    prefixes: AffixSet,
+    suffixes: AffixSet,
+    /// Index of the first suffix target.
+    suffix_offset: usize,
+    narrowing: AffixSet,
+    narrowed_suffixes: usize,
+    /// Lengths by index, to skip prefix and suffix pairs that would overlap.
+    narrowing_lens: Vec<usize>,
+    suffix_lens: Vec<usize>,
+    contains: Option<ContainsSet>,
+    patterns: Vec<AddressPattern>,
+    chain: Chain,
+    eip55: bool,
+    /// Arithmetic suffix pre-check, used when every hit must end with a suffix.
+    residue: Option<ResidueMatcher>,
+    score: Option<&'a ScoreTarget>,
+}
+
+impl<'a> Matcher<'a> {
+    pub(crate) fn new(targets: &'a SearchTargets, filled: &'a [AtomicBool]) -> Self {
+        Self {
+            filled,
+            prefixes: AffixSet::new(&targets.prefixes, |i| targets.prefix_ignores_case(i)),
+            suffixes: AffixSet::new(&targets.suffixes, |i| targets.suffix_ignores_case(i)),
+            suffix_offset: targets.prefixes.len(),
+            narrowing: AffixSet::new(&targets.narrowing_prefixes, |_| targets.ignore_case),
+            narrowed_suffixes: targets.narrowed_suffixes,
+            narrowing_lens: targets.narrowing_prefixes.iter().map(String::len).collect(),
+            suffix_lens: targets.suffixes.iter().map(String::len).collect(),
+            contains: (!targets.contains.is_empty())
+                .then(|| ContainsSet::new(&targets.contains, |i| targets.contains_ignores_case(i))),
+            patterns: targets
+                .patterns
+                .iter()
+                .map(|p| {
+                    AddressPattern::compile(p, targets.ignore_case).expect("pattern was validated")
+                })
+                .collect(),
+            chain: targets.chain,
+            eip55: targets.eip55,
+            // Prefixes, substrings and patterns are alternatives that need the full
+            // string anyway, and case folding has no single residue per suffix.
+            // Residues are Base58 only.
+            residue: if targets.chain == Chain::Tron
+                && targets.prefixes.is_empty()
+                && targets.contains.is_empty()
+                && targets.patterns.is_empty()
+                && (0..targets.suffixes.len()).all(|i| !targets.suffix_ignores_case(i))
+            {
+                ResidueMatcher::new(&targets.suffixes)
+            } else {
+                None
+            },
+            score: targets.score.as_ref(),
         }
     }
 
-    groups.sort_by_key(|g| g.len);
-    groups
+    /// Checks an uncompressed public key. Returns the address as wallets display it
+    /// and the index of the matched target.
+    #[inline]
+    pub(crate) fn check_key(&self, pk_uncompressed: &[u8; 65]) -> Option<(String, usize)> {
+        match self.chain {
+            Chain::Tron => self.check_payload(&tron_payload_from_uncompressed(pk_uncompressed)),
+            Chain::Evm => {
+                let address = evm_address_bytes(pk_uncompressed);
+                if self.eip55 {
+                    let checksummed = to_checksum_address(&address);
+                    let target = self.find_match(&checksummed)?;
+                    Some((checksummed, target))
+                } else {
+                    // Plain hex is matched in lowercase; only hits get the checksum.
+                    let target = self.find_match(&to_lower_address(&address))?;
+                    Some((to_checksum_address(&address), target))
+                }
+            }
+            Chain::Btc | Chain::BtcSegwit | Chain::BtcTaproot => {
+                let address = self.chain.address_from_uncompressed(pk_uncompressed);
+                let target = self.find_match(&address)?;
+                Some((address, target))
+            }
+        }
+    }
+
+    /// Checks a checksummed address payload, only Base58-encoding it when the
+    /// arithmetic suffix check (if any) passes. Returns the address and the index
+    /// of the matched target.
+    #[inline]
+    fn check_payload(&self, payload: &[u8; 25]) -> Option<(String, usize)> {
+        if let Some(residue) = &self.residue {
+            if !residue.is_match(payload) {
+                return None;
+            }
+        }
+        let address = bs58::encode(payload).into_string();
+        let target = self.find_match(&address)?;
+        Some((address, target))
+    }
+
+    #[inline]
+    fn is_filled(&self, target: usize) -> bool {
+        self.filled[target].load(Ordering::Relaxed)
+    }
+
+    /// When both prefixes and suffixes are given, an address must match one of each.
+    /// Each substring and regex/glob pattern is an independent alternative to the
+    /// prefix/suffix target and only runs when the cheaper checks don't hit.
+    #[inline]
+    pub(crate) fn find_match(&self, address: &str) -> Option<usize> {
+        let address_bytes = address.as_bytes();
+        let lead = self.chain.lead().len();
+
+        // Score mode has no targets; every candidate counts towards a single one.
+        if let Some(score) = self.score {
+            return score.is_candidate(&address[lead..]).then_some(0);
+        }
+
+        let affix_match = self
+            .prefixes
+            .find_prefix(address_bytes, lead, |i| self.is_filled(i))
+            .or_else(|| {
+                // Narrowed suffixes need a narrowing prefix. Shorter prefixes are
+                // tried first, and leave the most room for a suffix that doesn't
+                // overlap them.
+                let room = if self.narrowed_suffixes == 0 {
+                    None
+                } else {
+                    self.narrowing
+                        .find_prefix(address_bytes, lead, |_| false)
+                        .map(|prefix| address.len() - lead - self.narrowing_lens[prefix])
+                };
+                if room.is_none() && self.narrowed_suffixes == self.suffix_lens.len() {
+                    return None;
+                }
+                let offset = self.suffix_offset;
+                self.suffixes
+                    .find_suffix(address_bytes, |i| {
+                        self.is_filled(offset + i)
+                            || (i < self.narrowed_suffixes
+                                && room.is_none_or(|room| self.suffix_lens[i] > room))
+                    })
+                    .map(|i| offset + i)
+            });
+
+        let pattern_offset = self.filled.len() - self.patterns.len();
+        affix_match
+            .or_else(|| {
+                let contains = self.contains.as_ref()?;
+                let offset = pattern_offset - contains.len();
+                contains
+                    .find(&address_bytes[lead..], |i| self.is_filled(offset + i))
+                    .map(|i| offset + i)
+            })
+            .or_else(|| {
+                self.patterns
+                    .iter()
+                    .enumerate()
+                    .position(|(i, p)| !self.is_filled(pattern_offset + i) && p.is_match(address))
+                    .map(|i| pattern_offset + i)
+            })
+    }
+}
+
+/// The attempt counter shared by all workers, with the `--max-attempts` budget.
+///
+/// Workers add each batch as they finish it and stop the search themselves once the
+/// budget is used up, so it is overshot by less than one batch per thread: 1024
+/// keys for the random and walk strategies, `--mnemonic-indices` for mnemonics.
+#[derive(Clone)]
+pub struct Attempts {
+    count: Arc<AtomicU64>,
+    max: Option<u64>,
+}
+
+impl Attempts {
+    pub fn new(count: Arc<AtomicU64>, max: Option<u64>) -> Self {
+        Self { count, max }
+    }
+
+    /// Counts a finished batch, and sets `should_stop` once the budget is used up.
+    pub(crate) fn add(&self, batch: u64, should_stop: &AtomicBool) {
+        let total = self.count.fetch_add(batch, Ordering::Relaxed) + batch;
+        if self.max.is_some_and(|max| total >= max) {
+            should_stop.store(true, Ordering::Relaxed);
+        }
+    }
 }
 
 /// The main search function, optimized to reuse contexts.
 ///
 /// It uses `rayon::scope` to create a pool of long-running worker threads.
-/// Each thread initializes its `Secp256k1` context and RNG once, which provides
-/// a major performance boost.
+/// Each thread initializes its `Secp256k1` context (or key walker), RNG and
+/// matcher once, which provides a major performance boost.
+///
+/// Threads keep searching after a hit and stream every result through `sender`
+/// until `should_stop` is set. Targets flagged in `filled` are no longer reported.
 pub fn search(
-    suffixes: Vec<String>,
+    targets: SearchTargets,
+    keys: KeyOptions,
     sender: Sender<FoundWallet>,
     should_stop: &AtomicBool,
-    attempts: &Arc<AtomicU64>,
+    filled: &[AtomicBool],
+    attempts: &Attempts,
     num_threads: usize,
 ) {
-    // Pre-process suffixes for efficient lookup.
-    let suffix_groups = Arc::new(group_suffixes_by_length(&suffixes));
+    let targets = &targets;
 
     rayon::scope(|s| {
         for _ in 0..num_threads {
-            // Clone Arcs for each thread
             let sender_clone = sender.clone();
-            let attempts_clone = attempts.clone();
-            let suffix_groups_clone = Arc::clone(&suffix_groups);
 
             s.spawn(move |_| {
-                // --- Per-thread Initialization (Major Optimization) ---
-                let secp = Secp256k1::new();
-                let mut rng = rand::thread_rng();
-                // ----------------------------------------------------
-                let mut local_attempts: u64 = 0;
-
-                // Inner hot loop
-                loop {
-                    if should_stop.load(Ordering::Relaxed) {
-                        break;
+                let matcher = Matcher::new(targets, filled);
+                let base = targets.split_key.as_ref();
+                match keys.strategy {
+                    KeyStrategy::Random => {
+                        search_random(&matcher, base, &sender_clone, should_stop, attempts)
                     }
-
-                    local_attempts += 1;
-                    if local_attempts == 1024 {
-                        attempts_clone.fetch_add(local_attempts, Ordering::Relaxed);
-                        local_attempts = 0;
+                    KeyStrategy::Walk => {
+                        search_walk(&matcher, base, &sender_clone, should_stop, attempts)
                     }
+                    KeyStrategy::Mnemonic => search_mnemonic(
+                        &matcher,
+                        targets.chain,
+                        keys,
+                        &sender_clone,
+                        should_stop,
+                        attempts,
+                    ),
+                }
+            });
+        }
+    });
+}
 
-                    // Generate keys and the full address string
-                    let (private_key, public_key) = secp.generate_keypair(&mut rng);
-                    let address = public_key_to_tron_address(&public_key);
-                    let address_bytes = address.as_bytes();
-                    let addr_len = address_bytes.len();
-
-                    // Check for suffix matches on the generated string.
-                    for group in suffix_groups_clone.iter() {
-                        if addr_len < group.len {
-                            continue;
-                        }
-
-                        let suffix_slice = &address_bytes[addr_len - group.len..];
-                        for &candidate in &group.values {
-                            if suffix_slice == candidate {
-                                if local_attempts > 0 {
-                                    attempts_clone.fetch_add(local_attempts, Ordering::Relaxed);
-                                }
-                                // --- Match Found! ---
-                                let found = FoundWallet {
-                                    address, // Move the address
-                                    private_key_hex: private_key.display_secret().to_string(),
-                                };
-                                // Send the result and stop searching on this thread.
-                                let _ = sender_clone.send(found);
-                                return; // Exit thread
-                            }
-                        }
+/// Generates an independent random keypair (or offset from `base`) for every attempt.
+fn search_random(
+    matcher: &Matcher,
+    base: Option<&PublicKey>,
+    sender: &Sender<FoundWallet>,
+    should_stop: &AtomicBool,
+    attempts: &Attempts,
+) {
+    // --- Per-thread Initialization (Major Optimization) ---
+    let secp = Secp256k1::new();
+    let mut rng = rand::thread_rng();
+    // ----------------------------------------------------
+    let mut local_attempts: u64 = 0;
+
+    // Inner hot loop
+    loop {
+        if should_stop.load(Ordering::Relaxed) {
+            break;
+        }
+
+        local_attempts += 1;
+        if local_attempts == 1024 {
+            attempts.add(local_attempts, should_stop);
+            local_attempts = 0;
+        }
+
+        // Generate keys; for Tron the address string is only built if the residue check passes.
+        let (mut private_key, public_key) = match base {
+            None => secp.generate_keypair(&mut rng),
+            Some(base) => {
+                let mut offset = SecretKey::new(&mut rng);
+                match offset_public_key(base, &offset) {
+                    Ok(public_key) => (offset, public_key),
+                    // Only when the offset cancels out the base point exactly.
+                    Err(_) => {
+                        offset.non_secure_erase();
+                        continue;
                     }
                 }
+            }
+        };
+
+        if let Some((address, target)) = matcher.check_key(&public_key.serialize_uncompressed()) {
+            // --- Match Found! ---
+            let found = FoundWallet {
+                address, // Move the address
+                secret: SecretBytes::from(&private_key),
+                target,
+                mnemonic: None,
+            };
+            // Send the result and keep searching; the main thread decides when to stop.
+            if sender.send(found).is_err() {
+                break;
+            }
+        }
+        private_key.non_secure_erase();
+    }
+
+    if local_attempts > 0 {
+        attempts.add(local_attempts, should_stop);
+    }
+}
+
+/// Walks consecutive keys from a random starting scalar, see [`KeyWalker`].
+fn search_walk(
+    matcher: &Matcher,
+    base: Option<&PublicKey>,
+    sender: &Sender<FoundWallet>,
+    should_stop: &AtomicBool,
+    attempts: &Attempts,
+) {
+    let mut rng = rand::thread_rng();
+    let mut walker = match base {
+        None => KeyWalker::new(&mut rng),
+        Some(base) => {
+            let base = k256::PublicKey::from_sec1_bytes(&base.serialize())
+                .expect("secp256k1 public keys are valid SEC1 points");
+            KeyWalker::offset_from(base.to_projective(), &mut rng)
+        }
+    };
+
+    while !should_stop.load(Ordering::Relaxed) {
+        walker.advance();
+
+        for index in 0..WALK_BATCH {
+            if let Some((address, target)) = matcher.check_key(&walker.public_key(index)) {
+                let found = FoundWallet {
+                    address,
+                    secret: SecretBytes::new(walker.secret_key(index)),
+                    target,
+                    mnemonic: None,
+                };
+                if sender.send(found).is_err() {
+                    return;
+                }
+            }
+        }
+        attempts.add(WALK_BATCH as u64, should_stop);
+    }
+}
+
+/// Generates random mnemonics and checks the first `mnemonic_indices` addresses of
+/// each along the chain's usual HD path, see [`MnemonicKeys`].
+fn search_mnemonic(
+    matcher: &Matcher,
+    chain: Chain,
+    keys: KeyOptions,
+    sender: &Sender<FoundWallet>,
+    should_stop: &AtomicBool,
+    attempts: &Attempts,
+) {
+    let mut rng = rand::thread_rng();
+    let (purpose, coin_type) = chain.bip44_path();
 
-                if local_attempts > 0 {
-                    attempts_clone.fetch_add(local_attempts, Ordering::Relaxed);
+    while !should_stop.load(Ordering::Relaxed) {
+        let mnemonic = MnemonicKeys::random(&mut rng, keys.mnemonic_words, purpose, coin_type);
+
+        for index in 0..keys.mnemonic_indices {
+            let Some((mut private_key, public_key)) = mnemonic.key(index) else {
+                continue;
+            };
+            if let Some((address, target)) = matcher.check_key(&public_key.serialize_uncompressed())
+            {
+                let found = FoundWallet {
+                    address,
+                    secret: SecretBytes::from(&private_key),
+                    target,
+                    mnemonic: Some(mnemonic.origin(index)),
+                };
+                if sender.send(found).is_err() {
+                    return;
                 }
-            });
+            }
+            private_key.non_secure_erase();
         }
-    });
+        attempts.add(keys.mnemonic_indices as u64, should_stop);
+    }
 }